@@ -0,0 +1,59 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use robotxt::Robots;
+
+static TXT: &[u8] = b"User-Agent: foobot\n\
+    Disallow: /search\n\
+    Disallow: /*.php$\n\
+    Allow: /search/about\n\
+    Disallow: /private/\n\
+    Allow: /private/public/";
+
+fn paths() -> Vec<String> {
+    let paths = (0..1000).map(|u| match u % 4 {
+        0 => format!("/search/{u}?q=robots"),
+        1 => format!("/private/public/{u}.html"),
+        2 => format!("/index/{u}.php"),
+        _ => format!("/tëst/{u} path"),
+    });
+
+    paths.collect()
+}
+
+fn wildcards() -> String {
+    let rules = (0..1000).map(|u| match u % 2 {
+        0 => format!("Disallow: /*/{u}.php$\n"),
+        _ => format!("Allow: /*q={u}*\n"),
+    });
+
+    "User-Agent: foobot\n".to_string() + &rules.collect::<String>()
+}
+
+fn check(c: &mut Criterion) {
+    let paths = paths();
+    let wildcards = Robots::from_slice(wildcards().as_bytes(), "foobot");
+    let cases = [
+        ("rules", Robots::from_slice(TXT, "foobot")),
+        ("wildcards", wildcards),
+        ("always", Robots::from_always(true, "foobot")),
+    ];
+
+    for (name, r) in cases {
+        let mut group = c.benchmark_group(name);
+        group.bench_function("is_allowed", |b| {
+            b.iter(|| {
+                let paths = paths.iter().map(|u| r.is_allowed(black_box(u)));
+                paths.collect::<Vec<_>>()
+            })
+        });
+
+        group.bench_function("check_many", |b| {
+            b.iter(|| r.check_many(paths.iter().map(|u| black_box(u.as_str()))))
+        });
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, check);
+criterion_main!(benches);
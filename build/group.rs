@@ -0,0 +1,179 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::time::Duration;
+
+use crate::parse::normalize_path;
+
+/// Removes line breaks and comments from the directive value.
+pub(crate) fn sanitize_value(value: &str) -> String {
+    let value = value.split(['\r', '\n', '#']).next();
+    value.unwrap_or_default().trim().to_string()
+}
+
+/// Writes the comment, prefixing every line with the `#` character.
+pub(crate) fn write_comment(f: &mut Formatter<'_>, comment: &str) -> FmtResult {
+    for line in comment.lines() {
+        match line.trim().is_empty() {
+            true => writeln!(f, "#")?,
+            false => writeln!(f, "# {}", line.trim_end())?,
+        }
+    }
+
+    Ok(())
+}
+
+/// The `GroupRule` struct represents a single `allow` or `disallow` directive.
+#[derive(Debug, Clone)]
+struct GroupRule {
+    pattern: String,
+    allow: bool,
+}
+
+/// The `GroupBuilder` struct provides a convenient way to build
+/// a single group of directives of the `robots.txt` file.
+/// See [crate::RobotsBuilder::group].
+#[derive(Debug, Clone, Default)]
+pub struct GroupBuilder {
+    user_agents: Vec<String>,
+    rules: Vec<GroupRule>,
+    delay: Option<Duration>,
+    header: Option<String>,
+    footer: Option<String>,
+}
+
+impl GroupBuilder {
+    /// Creates a new `GroupBuilder` with the specified user-agents.
+    pub fn new<'a>(user_agents: impl IntoIterator<Item = &'a str>) -> Self {
+        let user_agents = user_agents.into_iter().map(sanitize_value);
+        let user_agents = user_agents.map(|u| u.to_lowercase());
+        let user_agents = user_agents.filter(|u| !u.is_empty());
+
+        Self {
+            user_agents: user_agents.collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Adds the comment before the group.
+    pub fn header(mut self, header: &str) -> Self {
+        self.header = Some(header.to_string());
+        self
+    }
+
+    /// Adds the comment after the group.
+    pub fn footer(mut self, footer: &str) -> Self {
+        self.footer = Some(footer.to_string());
+        self
+    }
+
+    /// Adds the `allow` directive with the specified pattern.
+    pub fn allow(self, pattern: &str) -> Self {
+        self.rule(pattern, true)
+    }
+
+    /// Adds the `disallow` directive with the specified pattern.
+    /// NOTE: Empty pattern allows everything.
+    pub fn disallow(self, pattern: &str) -> Self {
+        self.rule(pattern, false)
+    }
+
+    /// Adds the `allow` or `disallow` directive with the specified pattern.
+    fn rule(mut self, pattern: &str, allow: bool) -> Self {
+        let pattern = sanitize_value(pattern);
+
+        // Empty disallow is equivalent to allow all.
+        let pattern = match pattern.is_empty() && !allow {
+            false => normalize_path(pattern.as_str()),
+            true => String::new(),
+        };
+
+        self.rules.push(GroupRule { pattern, allow });
+        self
+    }
+
+    /// Sets the crawl-delay of the group.
+    /// NOTE: Overrides the previously set value.
+    pub fn crawl_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+}
+
+impl Display for GroupBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if let Some(header) = &self.header {
+            write_comment(f, header)?;
+        }
+
+        match self.user_agents.is_empty() {
+            true => writeln!(f, "User-Agent: *")?,
+            false => {
+                for user_agent in &self.user_agents {
+                    writeln!(f, "User-Agent: {user_agent}")?;
+                }
+            }
+        }
+
+        if let Some(delay) = &self.delay {
+            writeln!(f, "Crawl-Delay: {}", delay.as_secs_f64())?;
+        }
+
+        // Groups without rules would be merged with the next group.
+        if self.rules.is_empty() {
+            writeln!(f, "Disallow:")?;
+        }
+
+        for rule in &self.rules {
+            match (rule.allow, rule.pattern.is_empty()) {
+                (true, _) => writeln!(f, "Allow: {}", rule.pattern)?,
+                (false, true) => writeln!(f, "Disallow:")?,
+                (false, false) => writeln!(f, "Disallow: {}", rule.pattern)?,
+            }
+        }
+
+        if let Some(footer) = &self.footer {
+            write_comment(f, footer)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod rendering {
+    use super::*;
+
+    #[test]
+    fn simple() {
+        let g = GroupBuilder::new(["FooBot"])
+            .crawl_delay(Duration::from_secs(5))
+            .allow("/example/")
+            .disallow("example/nope.txt");
+
+        let txt = "User-Agent: foobot\n\
+            Crawl-Delay: 5\n\
+            Allow: /example/\n\
+            Disallow: /example/nope.txt\n";
+        assert_eq!(g.to_string(), txt);
+    }
+
+    #[test]
+    fn empty() {
+        let g = GroupBuilder::new([]);
+        assert_eq!(g.to_string(), "User-Agent: *\nDisallow:\n");
+
+        let g = GroupBuilder::new(["foobot"]).disallow("");
+        assert_eq!(g.to_string(), "User-Agent: foobot\nDisallow:\n");
+    }
+
+    #[test]
+    fn sanitized() {
+        let g = GroupBuilder::new(["foobot\nAllow: /"])
+            .disallow("/a b#c")
+            .disallow("/d\nAllow: /");
+
+        let txt = "User-Agent: foobot\n\
+            Disallow: /a%20b\n\
+            Disallow: /d\n";
+        assert_eq!(g.to_string(), txt);
+    }
+}
@@ -1,3 +1,5 @@
+mod group;
 mod state;
 
+pub use group::*;
 pub use state::*;
@@ -1,5 +1,158 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use url::Url;
+
+use crate::build::{write_comment, GroupBuilder};
+
+/// The `RobotsBuilder` struct provides a convenient way to build
+/// the `robots.txt` file from the provided directives.
 ///
-#[derive(Debug)]
-pub struct RobotsBuilder {}
+/// ```rust
+/// use robotxt::RobotsBuilder;
+///
+/// let txt = RobotsBuilder::default()
+///     .group(["foobot"], |u| u.allow("/example/").disallow("/example/nope.txt"))
+///     .sitemap("https://example.com/sitemap.xml".try_into().unwrap())
+///     .to_string();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RobotsBuilder {
+    groups: Vec<GroupBuilder>,
+    sitemaps: Vec<Url>,
+    header: Option<String>,
+    footer: Option<String>,
+}
+
+impl RobotsBuilder {
+    /// Creates a new empty `RobotsBuilder`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the comment at the beginning of the file.
+    pub fn header(mut self, header: &str) -> Self {
+        self.header = Some(header.to_string());
+        self
+    }
+
+    /// Adds the comment at the end of the file.
+    pub fn footer(mut self, footer: &str) -> Self {
+        self.footer = Some(footer.to_string());
+        self
+    }
+
+    /// Adds the new group of directives for the specified user-agents.
+    /// NOTE: Empty list of user-agents is equivalent to `*`.
+    pub fn group<'a>(
+        mut self,
+        user_agents: impl IntoIterator<Item = &'a str>,
+        group: impl FnOnce(GroupBuilder) -> GroupBuilder,
+    ) -> Self {
+        let builder = GroupBuilder::new(user_agents);
+        self.groups.push(group(builder));
+        self
+    }
+
+    /// Adds the `sitemap` directive.
+    pub fn sitemap(mut self, sitemap: Url) -> Self {
+        self.sitemaps.push(sitemap);
+        self
+    }
+}
+
+impl Display for RobotsBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let mut first = true;
+        let mut separate = |f: &mut Formatter<'_>| match first {
+            true => {
+                first = false;
+                Ok(())
+            }
+            false => writeln!(f),
+        };
+
+        if let Some(header) = &self.header {
+            separate(f)?;
+            write_comment(f, header)?;
+        }
+
+        for group in &self.groups {
+            separate(f)?;
+            Display::fmt(group, f)?;
+        }
+
+        if !self.sitemaps.is_empty() {
+            separate(f)?;
+            for sitemap in &self.sitemaps {
+                writeln!(f, "Sitemap: {sitemap}")?;
+            }
+        }
+
+        if let Some(footer) = &self.footer {
+            separate(f)?;
+            write_comment(f, footer)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod roundtrip {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::Robots;
+
+    fn build() -> String {
+        RobotsBuilder::new()
+            .header("Robots.txt: Start")
+            .group(["foobot"], |u| {
+                u.crawl_delay(Duration::from_millis(1500))
+                    .allow("/example/")
+                    .disallow("/example/nope.txt")
+                    .disallow("/*.php$")
+            })
+            .group(["barbot", "nombot"], |u| u.disallow("/"))
+            .group([], |u| u.disallow(""))
+            .sitemap("https://example.com/sitemap.xml".try_into().unwrap())
+            .footer("Robots.txt: End")
+            .to_string()
+    }
+
+    #[test]
+    fn rendering() {
+        let txt = "# Robots.txt: Start\n\n\
+            User-Agent: foobot\n\
+            Crawl-Delay: 1.5\n\
+            Allow: /example/\n\
+            Disallow: /example/nope.txt\n\
+            Disallow: /*.php$\n\n\
+            User-Agent: barbot\n\
+            User-Agent: nombot\n\
+            Disallow: /\n\n\
+            User-Agent: *\n\
+            Disallow:\n\n\
+            Sitemap: https://example.com/sitemap.xml\n\n\
+            # Robots.txt: End\n";
+        assert_eq!(build(), txt);
+    }
+
+    #[test]
+    fn parsing() {
+        let txt = build();
+
+        let r = Robots::from_slice(txt.as_bytes(), "foobot");
+        assert_eq!(r.crawl_delay(), Some(Duration::from_millis(1500)));
+        assert!(r.is_allowed("/example/yeah.txt"));
+        assert!(!r.is_allowed("/example/nope.txt"));
+        assert!(!r.is_allowed("/index.php"));
+        assert_eq!(r.sitemaps().len(), 1);
+
+        let r = Robots::from_slice(txt.as_bytes(), "nombot");
+        assert!(!r.is_allowed("/example/yeah.txt"));
 
-impl RobotsBuilder {}
+        let r = Robots::from_slice(txt.as_bytes(), "super-bot");
+        assert!(r.is_allowed("/example/nope.txt"));
+    }
+}
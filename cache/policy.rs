@@ -0,0 +1,139 @@
+use std::cmp::min;
+use std::time::Duration;
+
+/// The maximum time the cached `robots.txt` file should be used for.
+/// See [Request for Comments: 9309](https://www.rfc-editor.org/rfc/rfc9309.html#section-2.4).
+pub const MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The time the unreachable `robots.txt` file is treated as full disallow for.
+/// See [Request for Comments: 9309](https://www.rfc-editor.org/rfc/rfc9309.html#section-2.3.1.4).
+pub const UNREACHABLE_PERIOD: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// The `CachePolicy` struct represents the expiration settings
+/// of the [crate::RobotsCache].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CachePolicy {
+    max_age: Duration,
+    retry: Duration,
+    unreachable_period: Duration,
+}
+
+impl Default for CachePolicy {
+    fn default() -> Self {
+        Self {
+            max_age: MAX_AGE,
+            retry: Duration::from_secs(60 * 60),
+            unreachable_period: UNREACHABLE_PERIOD,
+        }
+    }
+}
+
+impl CachePolicy {
+    /// Sets the maximum age of the cached file.
+    /// NOTE: Can not exceed 24 hours, the retry time is reduced to it.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = min(max_age, MAX_AGE);
+        self.retry = min(self.retry, self.max_age);
+        self
+    }
+
+    /// Sets the time before the unreachable file is fetched again.
+    /// NOTE: Can not exceed the maximum age.
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = min(retry, self.max_age);
+        self
+    }
+
+    /// Sets the time the unreachable file is treated as full disallow for.
+    pub fn unreachable_period(mut self, period: Duration) -> Self {
+        self.unreachable_period = period;
+        self
+    }
+
+    /// Returns true if the file was unreachable for long enough to
+    /// fall back to the last successfully fetched copy.
+    pub fn falls_back(&self, unreachable_for: Duration) -> bool {
+        unreachable_for >= self.unreachable_period
+    }
+
+    /// Returns the time the fetched file is fresh for, respecting
+    /// the `Cache-Control` header value (if any).
+    pub fn fresh_for(&self, cache_control: Option<&str>) -> Duration {
+        let age = cache_control.and_then(parse_cache_control);
+        min(age.unwrap_or(self.max_age), self.max_age)
+    }
+
+    /// Returns the time the unreachable file is fresh for, respecting
+    /// the `Cache-Control` header value (if any).
+    pub fn retry_for(&self, cache_control: Option<&str>) -> Duration {
+        min(self.fresh_for(cache_control), self.retry)
+    }
+}
+
+/// Parses the `Cache-Control` header value into the maximum age.
+/// Returns `None` if the header does not restrict the age.
+pub fn parse_cache_control(value: &str) -> Option<Duration> {
+    let mut age = None;
+    for directive in value.split(',') {
+        let directive = directive.trim().to_ascii_lowercase();
+        let (key, value) = match directive.split_once('=') {
+            Some((key, value)) => (key.trim().to_string(), Some(value.trim())),
+            None => (directive.clone(), None),
+        };
+
+        match (key.as_str(), value) {
+            ("no-store" | "no-cache", _) => return Some(Duration::ZERO),
+            ("max-age", Some(value)) => {
+                let value = value.trim_matches('"').parse::<u64>().ok();
+                age = value.map(Duration::from_secs).or(age);
+            }
+            _ => {}
+        }
+    }
+
+    age
+}
+
+#[cfg(test)]
+mod expiration {
+    use super::*;
+
+    #[test]
+    fn headers() {
+        let age = parse_cache_control("public, max-age=3600");
+        assert_eq!(age, Some(Duration::from_secs(3600)));
+
+        let age = parse_cache_control("max-age=\"60\", no-cache");
+        assert_eq!(age, Some(Duration::ZERO));
+
+        assert_eq!(parse_cache_control("public"), None);
+        assert_eq!(parse_cache_control("max-age=abc"), None);
+    }
+
+    #[test]
+    fn bounded() {
+        let p = CachePolicy::default();
+        assert_eq!(p.fresh_for(None), MAX_AGE);
+        assert_eq!(p.fresh_for(Some("max-age=31536000")), MAX_AGE);
+        assert_eq!(p.fresh_for(Some("max-age=60")), Duration::from_secs(60));
+        assert_eq!(p.retry_for(None), Duration::from_secs(3600));
+
+        let p = p.max_age(Duration::from_secs(365 * 24 * 60 * 60));
+        assert_eq!(p.fresh_for(None), MAX_AGE);
+    }
+
+    #[test]
+    fn retry() {
+        let days = Duration::from_secs(48 * 60 * 60);
+        let p = CachePolicy::default().retry(days);
+        assert_eq!(p.retry, MAX_AGE);
+
+        let minute = Duration::from_secs(60);
+        let p = p.max_age(minute);
+        assert_eq!(p.retry, minute);
+
+        let p = p.retry(Duration::from_secs(3600));
+        assert_eq!(p.retry, minute);
+        assert_eq!(p.retry_for(None), minute);
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+use url::{Origin, Url};
+
+use crate::cache::CachePolicy;
+use crate::parse::{AccessResult, Robots};
+
+/// The `CacheEntry` struct represents the cached `robots.txt` file
+/// of the single origin.
+#[derive(Debug, Clone)]
+pub struct CacheEntry {
+    robots: Robots,
+    fetched_at: SystemTime,
+    expires_at: SystemTime,
+    last_good: Option<(Robots, SystemTime)>,
+    unreachable_since: Option<SystemTime>,
+}
+
+impl CacheEntry {
+    /// Returns the effective `Robots` of the origin.
+    pub fn robots(&self) -> &Robots {
+        &self.robots
+    }
+
+    /// Returns the time of the last fetch attempt.
+    pub fn fetched_at(&self) -> SystemTime {
+        self.fetched_at
+    }
+
+    /// Returns the time the entry should be fetched again at.
+    pub fn expires_at(&self) -> SystemTime {
+        self.expires_at
+    }
+
+    /// Returns true if the entry should be fetched again.
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        now >= self.expires_at
+    }
+
+    /// Returns the last reachable `Robots` and the time it was fetched at.
+    pub fn last_good(&self) -> Option<(&Robots, SystemTime)> {
+        self.last_good.as_ref().map(|(r, t)| (r, *t))
+    }
+
+    /// Returns the time the origin became unreachable at (if it still is).
+    pub fn unreachable_since(&self) -> Option<SystemTime> {
+        self.unreachable_since
+    }
+}
+
+/// The `RobotsCache` struct provides the storage of the `robots.txt` files
+/// keyed by origin with [Request for Comments: 9309](https://www.rfc-editor.org/rfc/rfc9309.html#section-2.4)
+/// expiration semantics:
+///
+/// - fetched files are used for at most 24 hours (or less if `Cache-Control` says so).
+/// - unreachable files are treated as full disallow for the bounded period,
+///   then the last reachable copy is used (or full allow if there is none).
+///
+/// ```rust
+/// use std::time::SystemTime;
+///
+/// use robotxt::{AccessResult, RobotsCache};
+/// use url::Url;
+///
+/// let mut cache = RobotsCache::new("foobot");
+/// let url = Url::parse("https://example.com/robots.txt").unwrap();
+/// let now = SystemTime::now();
+///
+/// let txt = b"User-Agent: foobot\nDisallow: /";
+/// let access = AccessResult::Successful(txt);
+/// cache.insert(&url, access, Some("max-age=3600"), now);
+///
+/// let r = cache.get(&url, now).unwrap();
+/// assert!(!r.is_allowed("/example/nope.txt"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RobotsCache {
+    user_agent: String,
+    policy: CachePolicy,
+    entries: HashMap<Origin, CacheEntry>,
+}
+
+impl RobotsCache {
+    /// Creates a new `RobotsCache` for the user-agent with the default policy.
+    pub fn new(user_agent: &str) -> Self {
+        Self::with_policy(user_agent, CachePolicy::default())
+    }
+
+    /// Creates a new `RobotsCache` for the user-agent with the specified policy.
+    pub fn with_policy(user_agent: &str, policy: CachePolicy) -> Self {
+        Self {
+            user_agent: user_agent.to_string(),
+            policy,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Stores the result of the fetch attempt of the URL origin with
+    /// the `Cache-Control` header value (if any) and returns
+    /// the effective `Robots` of the origin.
+    pub fn insert(
+        &mut self,
+        url: &Url,
+        access: AccessResult,
+        cache_control: Option<&str>,
+        now: SystemTime,
+    ) -> &Robots {
+        let origin = url.origin();
+        let previous = self.entries.remove(&origin);
+        let (last_good, since) = match previous {
+            Some(entry) => (entry.last_good, entry.unreachable_since),
+            None => (None, None),
+        };
+
+        let entry = match access {
+            AccessResult::Unreachable => {
+                let since = since.unwrap_or(now);
+                let elapsed = now.duration_since(since).unwrap_or(Duration::ZERO);
+                let robots = match (self.policy.falls_back(elapsed), &last_good) {
+                    (false, _) => Robots::from_access(access, &self.user_agent),
+                    (true, Some((robots, _))) => robots.clone(),
+                    (true, None) => Robots::from_always(true, &self.user_agent),
+                };
+
+                CacheEntry {
+                    robots: robots.with_origin(url),
+                    fetched_at: now,
+                    expires_at: now + self.policy.retry_for(cache_control),
+                    last_good,
+                    unreachable_since: Some(since),
+                }
+            }
+
+            _ => {
+                let robots = Robots::from_access(access, &self.user_agent);
+                let robots = robots.with_origin(url);
+
+                CacheEntry {
+                    robots: robots.clone(),
+                    fetched_at: now,
+                    expires_at: now + self.policy.fresh_for(cache_control),
+                    last_good: Some((robots, now)),
+                    unreachable_since: None,
+                }
+            }
+        };
+
+        &self.entries.entry(origin).or_insert(entry).robots
+    }
+
+    /// Returns the `Robots` of the URL origin unless it is missing
+    /// or should be fetched again.
+    pub fn get(&self, url: &Url, now: SystemTime) -> Option<&Robots> {
+        let entry = self.entry(url)?;
+        match entry.is_expired(now) {
+            false => Some(entry.robots()),
+            true => None,
+        }
+    }
+
+    /// Returns the entry of the URL origin, even if it is expired.
+    pub fn entry(&self, url: &Url) -> Option<&CacheEntry> {
+        self.entries.get(&url.origin())
+    }
+
+    /// Removes the entry of the URL origin.
+    pub fn remove(&mut self, url: &Url) -> Option<CacheEntry> {
+        self.entries.remove(&url.origin())
+    }
+
+    /// Removes all expired entries.
+    /// NOTE: Removes the last reachable copies as well.
+    pub fn evict_expired(&mut self, now: SystemTime) {
+        self.entries.retain(|_, entry| !entry.is_expired(now));
+    }
+
+    /// Returns the number of cached origins.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if there are no cached origins.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod transitions {
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(60 * 60);
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn url() -> Url {
+        Url::parse("https://example.com/robots.txt").unwrap()
+    }
+
+    #[test]
+    fn expiration() {
+        let mut cache = RobotsCache::new("foobot");
+        let now = SystemTime::UNIX_EPOCH;
+
+        let access = AccessResult::Successful(b"Disallow: /a");
+        cache.insert(&url(), access, None, now);
+        assert!(cache.get(&url(), now + DAY - HOUR).is_some());
+        assert!(cache.get(&url(), now + DAY).is_none());
+
+        cache.insert(&url(), access, Some("max-age=60"), now);
+        assert!(cache.get(&url(), now + HOUR).is_none());
+
+        let other = Url::parse("http://example.com/robots.txt").unwrap();
+        assert!(cache.get(&other, now).is_none());
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn unreachable() {
+        let policy = CachePolicy::default().unreachable_period(DAY * 2);
+        let mut cache = RobotsCache::with_policy("foobot", policy);
+        let now = SystemTime::UNIX_EPOCH;
+
+        let access = AccessResult::Successful(b"Disallow: /a");
+        cache.insert(&url(), access, None, now);
+
+        let access = AccessResult::Unreachable;
+        let r = cache.insert(&url(), access, None, now + DAY);
+        assert_eq!(r.is_always(), Some(false));
+        assert!(cache.get(&url(), now + DAY + HOUR).is_none());
+
+        let r = cache.insert(&url(), access, None, now + DAY * 2);
+        assert_eq!(r.is_always(), Some(false));
+
+        let r = cache.insert(&url(), access, None, now + DAY * 3);
+        assert!(r.is_always().is_none());
+        assert!(!r.is_allowed("/a") && r.is_allowed("/b"));
+
+        let e = cache.entry(&url()).unwrap();
+        assert_eq!(e.unreachable_since(), Some(now + DAY));
+        assert_eq!(e.last_good().map(|(_, t)| t), Some(now));
+    }
+
+    #[test]
+    fn recovered() {
+        let mut cache = RobotsCache::new("foobot");
+        let now = SystemTime::UNIX_EPOCH;
+
+        cache.insert(&url(), AccessResult::Unreachable, None, now);
+        let r = cache.insert(&url(), AccessResult::Unavailable, None, now + HOUR);
+        assert_eq!(r.is_always(), Some(true));
+
+        let e = cache.entry(&url()).unwrap();
+        assert_eq!(e.unreachable_since(), None);
+
+        let policy = CachePolicy::default().unreachable_period(Duration::ZERO);
+        let mut cache = RobotsCache::with_policy("foobot", policy);
+        let r = cache.insert(&url(), AccessResult::Unreachable, None, now);
+        assert_eq!(r.is_always(), Some(true));
+    }
+}
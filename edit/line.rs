@@ -0,0 +1,259 @@
+use std::ops::Range;
+
+use bstr::ByteSlice;
+
+use crate::build::sanitize_value;
+use crate::parse::{into_directives_with, Dialect, Directive};
+
+/// The `LineKind` enum represents the kind of the single line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    UserAgent,
+    Allow,
+    Disallow,
+    CrawlDelay,
+    Sitemap,
+    CleanParam,
+    Host,
+    RequestRate,
+    VisitTime,
+    /// The line containing only the comment.
+    Comment,
+    /// The line containing only whitespaces.
+    Blank,
+    /// The line with no recognized directive.
+    Unknown,
+}
+
+impl LineKind {
+    /// Returns the canonical spelling of the directive key (if any).
+    pub fn key(&self) -> Option<&'static str> {
+        match self {
+            Self::UserAgent => Some("User-Agent"),
+            Self::Allow => Some("Allow"),
+            Self::Disallow => Some("Disallow"),
+            Self::CrawlDelay => Some("Crawl-Delay"),
+            Self::Sitemap => Some("Sitemap"),
+            Self::CleanParam => Some("Clean-Param"),
+            Self::Host => Some("Host"),
+            Self::RequestRate => Some("Request-Rate"),
+            Self::VisitTime => Some("Visit-Time"),
+            Self::Comment | Self::Blank | Self::Unknown => None,
+        }
+    }
+
+    /// Returns true if the line is the `allow` or `disallow` directive.
+    pub fn is_rule(&self) -> bool {
+        matches!(self, Self::Allow | Self::Disallow)
+    }
+}
+
+/// The `SyntaxLine` struct represents the single line of the `robots.txt` file
+/// with every byte of it, i.e. the original spelling and casing of the key,
+/// whitespaces, the inline comment and the line ending.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxLine {
+    kind: LineKind,
+    text: Vec<u8>,
+    key: Range<usize>,
+    value: Range<usize>,
+    // Generated lines use the line ending of the file.
+    ending: Option<Vec<u8>>,
+}
+
+impl SyntaxLine {
+    /// Creates a new `SyntaxLine` of the specified kind and value
+    /// with the canonical spelling of the key.
+    /// NOTE: The value is cut at the first line break.
+    pub fn new(kind: LineKind, value: &str) -> Self {
+        let first = value.lines().next().unwrap_or_default();
+        let (text, key, value) = match kind.key() {
+            Some(key) => {
+                let value = sanitize_value(value);
+                let text = match value.is_empty() {
+                    true => format!("{key}:"),
+                    false => format!("{key}: {value}"),
+                };
+
+                let (start, end) = (text.len() - value.len(), text.len());
+                (text, 0..key.len(), start..end)
+            }
+
+            None => {
+                let text = match kind {
+                    LineKind::Comment if first.trim().is_empty() => "#".to_string(),
+                    LineKind::Comment => format!("# {}", first.trim()),
+                    LineKind::Blank => String::new(),
+                    _ => first.to_string(),
+                };
+
+                (text, 0..0, 0..0)
+            }
+        };
+
+        let text = text.into_bytes();
+        let value = match kind {
+            LineKind::Comment | LineKind::Unknown => 0..text.len(),
+            _ => value,
+        };
+
+        Self {
+            kind,
+            text,
+            key,
+            value,
+            ending: None,
+        }
+    }
+
+    /// Creates a new `SyntaxLine` from the raw line and its ending.
+    pub(crate) fn from_raw(text: &[u8], ending: &[u8], dialect: Dialect) -> Self {
+        let token = into_directives_with(text, dialect).into_iter().next();
+        let (kind, key, value) = match token {
+            None => (LineKind::Blank, 0..0, 0..0),
+            Some(token) => {
+                let position = token.position();
+                let (key, value) = (position.key().range(), position.value().range());
+                let kind = match token.directive() {
+                    Directive::UserAgent(_) => LineKind::UserAgent,
+                    // Empty disallow is lexed as allow all.
+                    Directive::Allow(u) if !u.is_empty() && value.is_empty() => LineKind::Disallow,
+                    Directive::Allow(_) => LineKind::Allow,
+                    Directive::Disallow(_) => LineKind::Disallow,
+                    Directive::CrawlDelay(_) => LineKind::CrawlDelay,
+                    Directive::Sitemap(_) => LineKind::Sitemap,
+                    Directive::CleanParam(_) => LineKind::CleanParam,
+                    Directive::Host(_) => LineKind::Host,
+                    Directive::RequestRate(_) => LineKind::RequestRate,
+                    Directive::VisitTime(_) => LineKind::VisitTime,
+                    Directive::Unknown(u) if u.trim().is_empty() => LineKind::Blank,
+                    Directive::Unknown(u) if u.trim_start().starts_with(b"#") => LineKind::Comment,
+                    Directive::Unknown(_) => LineKind::Unknown,
+                };
+
+                (kind, key, value)
+            }
+        };
+
+        Self {
+            kind,
+            text: text.to_vec(),
+            key,
+            value,
+            ending: Some(ending.to_vec()),
+        }
+    }
+
+    /// Returns the kind of the line.
+    pub fn kind(&self) -> LineKind {
+        self.kind
+    }
+
+    /// Returns the original key of the directive.
+    /// NOTE: Empty for comments, blank and unknown lines.
+    pub fn key(&self) -> &[u8] {
+        &self.text[self.key.clone()]
+    }
+
+    /// Returns the trimmed value of the directive.
+    /// NOTE: The whole line for comments and unknown lines.
+    pub fn value(&self) -> &[u8] {
+        &self.text[self.value.clone()]
+    }
+
+    /// Returns the line without its ending.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.text
+    }
+
+    /// Returns the original line ending (if any).
+    /// NOTE: `None` for the generated lines.
+    pub fn ending(&self) -> Option<&[u8]> {
+        self.ending.as_deref()
+    }
+
+    /// Replaces the value of the directive and keeps the rest of the line.
+    /// NOTE: Does nothing for comments, blank and unknown lines.
+    pub fn set_value(&mut self, value: &str) {
+        if self.kind.key().is_none() {
+            return;
+        }
+
+        let value = sanitize_value(value).into_bytes();
+
+        // Separates the value from the bare key, e.g. `Disallow:`.
+        let start = self.value.start;
+        let separated = start > 0 && self.text[start - 1].is_ascii_whitespace();
+        if self.value.is_empty() && !value.is_empty() && !separated {
+            self.text.insert(start, b' ');
+            self.value = start + 1..start + 1;
+        }
+
+        let start = self.value.start;
+        let end = start + value.len();
+        self.text.splice(self.value.clone(), value);
+        self.value = start..end;
+    }
+
+    /// Writes the line and its ending or the default ending
+    /// if the line has none and is not the last one.
+    pub(crate) fn write_to(&self, buffer: &mut Vec<u8>, default: &[u8], last: bool) {
+        buffer.extend_from_slice(&self.text);
+        match &self.ending {
+            Some(ending) if !ending.is_empty() => buffer.extend_from_slice(ending),
+            Some(_) if last => {}
+            _ => buffer.extend_from_slice(default),
+        }
+    }
+}
+
+#[cfg(test)]
+mod lines {
+    use super::*;
+
+    fn line(text: &[u8]) -> SyntaxLine {
+        SyntaxLine::from_raw(text, b"\n", Dialect::default())
+    }
+
+    #[test]
+    fn kinds() {
+        assert_eq!(line(b"  DisAllow : /a # b").kind(), LineKind::Disallow);
+        assert_eq!(line(b"dissallow:").kind(), LineKind::Disallow);
+        assert_eq!(line(b"Allow:").kind(), LineKind::Allow);
+        assert_eq!(line(b"  # comment").kind(), LineKind::Comment);
+        assert_eq!(line(b" \t").kind(), LineKind::Blank);
+        assert_eq!(line(b"").kind(), LineKind::Blank);
+        assert_eq!(line(b"foo: bar").kind(), LineKind::Unknown);
+
+        let u = line(b"  DisAllow : /a # b");
+        assert_eq!((u.key(), u.value()), (&b"DisAllow"[..], &b"/a"[..]));
+    }
+
+    #[test]
+    fn generated() {
+        let u = SyntaxLine::new(LineKind::Disallow, "/a\nAllow: /");
+        assert_eq!(u.as_bytes(), b"Disallow: /a");
+        assert_eq!(u.value(), b"/a");
+        assert_eq!(u.ending(), None);
+
+        let u = SyntaxLine::new(LineKind::Comment, " note ");
+        assert_eq!(u.as_bytes(), b"# note");
+    }
+
+    #[test]
+    fn modified() {
+        let mut u = line(b"  DisAllow : /a # b");
+        u.set_value("/bc");
+        assert_eq!(u.as_bytes(), b"  DisAllow : /bc # b");
+        assert_eq!(u.value(), b"/bc");
+
+        let mut u = line(b"Disallow:");
+        u.set_value("/a");
+        assert_eq!(u.as_bytes(), b"Disallow: /a");
+        assert_eq!(u.value(), b"/a");
+
+        u.set_value("");
+        assert_eq!(u.as_bytes(), b"Disallow: ");
+        assert_eq!(u.value(), b"");
+    }
+}
@@ -0,0 +1,5 @@
+mod line;
+mod tree;
+
+pub use line::*;
+pub use tree::*;
@@ -0,0 +1,378 @@
+use crate::edit::{LineKind, SyntaxLine};
+use crate::parse::{b_strip_bom, Dialect};
+
+/// The `SyntaxGroup` struct represents the lines of the single group,
+/// i.e. its comments, `user-agent` lines and everything up to the next group.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyntaxGroup {
+    lines: Vec<SyntaxLine>,
+}
+
+impl SyntaxGroup {
+    /// Creates a new `SyntaxGroup` with the specified user-agents.
+    /// NOTE: Empty list of user-agents is equivalent to `*`.
+    pub fn new<'a>(user_agents: impl IntoIterator<Item = &'a str>) -> Self {
+        let user_agents = user_agents.into_iter();
+        let mut lines: Vec<_> = user_agents
+            .map(|u| SyntaxLine::new(LineKind::UserAgent, u))
+            .filter(|u| !u.value().is_empty())
+            .collect();
+
+        if lines.is_empty() {
+            lines.push(SyntaxLine::new(LineKind::UserAgent, "*"));
+        }
+
+        Self { lines }
+    }
+
+    /// Returns all lines of the group.
+    pub fn lines(&self) -> &[SyntaxLine] {
+        &self.lines
+    }
+
+    /// Returns all lines of the group for editing.
+    pub fn lines_mut(&mut self) -> &mut Vec<SyntaxLine> {
+        &mut self.lines
+    }
+
+    /// Returns the values of all `user-agent` lines.
+    pub fn user_agents(&self) -> impl Iterator<Item = &[u8]> {
+        let lines = self
+            .lines
+            .iter()
+            .filter(|u| u.kind() == LineKind::UserAgent);
+        lines.map(|u| u.value())
+    }
+
+    /// Returns all `allow` and `disallow` lines.
+    pub fn rules(&self) -> impl Iterator<Item = &SyntaxLine> {
+        self.lines.iter().filter(|u| u.kind().is_rule())
+    }
+
+    /// Adds the `allow` line after the last directive of the group.
+    pub fn allow(&mut self, pattern: &str) -> &mut Self {
+        self.push_directive(SyntaxLine::new(LineKind::Allow, pattern))
+    }
+
+    /// Adds the `disallow` line after the last directive of the group.
+    /// NOTE: Empty pattern allows everything.
+    pub fn disallow(&mut self, pattern: &str) -> &mut Self {
+        self.push_directive(SyntaxLine::new(LineKind::Disallow, pattern))
+    }
+
+    /// Inserts the line after the last directive, i.e. before
+    /// the trailing comments and blank lines.
+    fn push_directive(&mut self, line: SyntaxLine) -> &mut Self {
+        let trailing = |u: &SyntaxLine| matches!(u.kind(), LineKind::Comment | LineKind::Blank);
+        let index = self.lines.iter().rposition(|u| !trailing(u));
+        self.lines.insert(index.map_or(0, |u| u + 1), line);
+        self
+    }
+
+    /// Removes all `allow` or `disallow` lines with the specified pattern.
+    /// Returns true if any line was removed.
+    pub fn remove_rule(&mut self, pattern: &str, allow: bool) -> bool {
+        let kind = match allow {
+            true => LineKind::Allow,
+            false => LineKind::Disallow,
+        };
+
+        let len = self.lines.len();
+        let matches = |u: &SyntaxLine| u.kind() == kind && u.value() == pattern.as_bytes();
+        self.lines.retain(|u| !matches(u));
+        self.lines.len() != len
+    }
+}
+
+/// The `SyntaxTree` struct represents the lossless concrete syntax tree
+/// of the `robots.txt` file, i.e. every byte of the input including the BOM,
+/// comments, blank lines, original spellings, whitespaces and line endings.
+/// Unchanged parts are printed back byte-identical.
+///
+/// ```rust
+/// use robotxt::SyntaxTree;
+///
+/// let txt = b"# Crawlers\r\nuser-agent: foobot\r\ndissallow : /a # old\r\n";
+/// let mut tree = SyntaxTree::from_slice(txt);
+/// assert_eq!(tree.to_bytes(), txt);
+///
+/// let group = tree.group_mut("foobot").unwrap();
+/// group.lines_mut()[2].set_value("/b");
+/// group.allow("/b/c");
+///
+/// let edited = b"# Crawlers\r\nuser-agent: foobot\r\ndissallow : /b # old\r\nAllow: /b/c\r\n";
+/// assert_eq!(tree.to_bytes(), edited);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyntaxTree {
+    bom: Vec<u8>,
+    preamble: Vec<SyntaxLine>,
+    groups: Vec<SyntaxGroup>,
+    dialect: Dialect,
+}
+
+impl SyntaxTree {
+    /// Creates a new `SyntaxTree` from the byte slice.
+    pub fn from_slice(robots: &[u8]) -> Self {
+        Self::from_slice_with(robots, Dialect::default())
+    }
+
+    /// Creates a new `SyntaxTree` from the byte slice
+    /// recognizing the key spellings of the specified dialect.
+    /// NOTE: The input is never truncated.
+    pub fn from_slice_with(robots: &[u8], dialect: Dialect) -> Self {
+        // Removes the byte order mark (BOM) the same way the lexer does.
+        let bom = robots.len() - b_strip_bom(robots).len();
+
+        let mut tree = Self {
+            bom: robots[..bom].to_vec(),
+            dialect,
+            ..Self::default()
+        };
+
+        // Null characters are treated as line endings.
+        let mut captures_group = false;
+        for (text, ending) in split_lines(&robots[bom..]) {
+            let line = SyntaxLine::from_raw(text, ending, dialect);
+            match line.kind() {
+                LineKind::UserAgent if !captures_group => {
+                    tree.open_group();
+                    captures_group = true;
+                }
+
+                LineKind::Allow | LineKind::Disallow => captures_group = false,
+                LineKind::RequestRate | LineKind::VisitTime => captures_group = false,
+                LineKind::CrawlDelay if dialect.supports_crawl_delay() => captures_group = false,
+                _ => {}
+            }
+
+            match tree.groups.last_mut() {
+                Some(group) => group.lines.push(line),
+                None => tree.preamble.push(line),
+            }
+        }
+
+        tree
+    }
+
+    /// Opens the new group and moves the comments directly preceding it
+    /// from the end of the previous group.
+    fn open_group(&mut self) {
+        let previous = match self.groups.last_mut() {
+            Some(group) => &mut group.lines,
+            None => &mut self.preamble,
+        };
+
+        let comments = previous.iter().rev();
+        let comments = comments.take_while(|u| u.kind() == LineKind::Comment);
+        let lines = previous.split_off(previous.len() - comments.count());
+        self.groups.push(SyntaxGroup { lines });
+    }
+
+    /// Returns the interpretation the tree was parsed with.
+    pub fn dialect(&self) -> Dialect {
+        self.dialect
+    }
+
+    /// Returns the lines preceding the first group.
+    pub fn preamble(&self) -> &[SyntaxLine] {
+        &self.preamble
+    }
+
+    /// Returns the lines preceding the first group for editing.
+    pub fn preamble_mut(&mut self) -> &mut Vec<SyntaxLine> {
+        &mut self.preamble
+    }
+
+    /// Returns all groups in the original order.
+    pub fn groups(&self) -> &[SyntaxGroup] {
+        &self.groups
+    }
+
+    /// Returns all groups for editing.
+    pub fn groups_mut(&mut self) -> &mut Vec<SyntaxGroup> {
+        &mut self.groups
+    }
+
+    /// Returns the first group with the specified user-agent (case-insensitive).
+    pub fn group(&self, user_agent: &str) -> Option<&SyntaxGroup> {
+        let index = self.find_group(user_agent)?;
+        Some(&self.groups[index])
+    }
+
+    /// Returns the first group with the specified user-agent for editing.
+    pub fn group_mut(&mut self, user_agent: &str) -> Option<&mut SyntaxGroup> {
+        let index = self.find_group(user_agent)?;
+        Some(&mut self.groups[index])
+    }
+
+    /// Returns the index of the first group with the specified user-agent.
+    fn find_group(&self, user_agent: &str) -> Option<usize> {
+        let user_agent = user_agent.as_bytes();
+        let mut groups = self.groups.iter();
+        groups.position(|g| g.user_agents().any(|u| u.eq_ignore_ascii_case(user_agent)))
+    }
+
+    /// Adds the group at the end of the file
+    /// separating it from the previous line with a blank line.
+    pub fn push_group(&mut self, mut group: SyntaxGroup) {
+        let last = self.lines().last().map(|u| u.kind());
+        if last.is_some_and(|u| u != LineKind::Blank) {
+            let blank = SyntaxLine::new(LineKind::Blank, "");
+            group.lines.insert(0, blank);
+        }
+
+        self.groups.push(group);
+    }
+
+    /// Removes all groups with the specified user-agent (case-insensitive).
+    /// Returns the number of removed groups.
+    pub fn remove_group(&mut self, user_agent: &str) -> usize {
+        let mut removed = 0;
+        while let Some(index) = self.find_group(user_agent) {
+            self.groups.remove(index);
+            removed += 1;
+        }
+
+        removed
+    }
+
+    /// Returns all lines in the original order.
+    pub fn lines(&self) -> impl Iterator<Item = &SyntaxLine> {
+        let groups = self.groups.iter().flat_map(|g| g.lines.iter());
+        self.preamble.iter().chain(groups)
+    }
+
+    /// Returns the line ending used by the file.
+    /// NOTE: Defaults to `\n` if the file has no line breaks.
+    fn line_ending(&self) -> &[u8] {
+        let endings = self.lines().filter_map(|u| u.ending());
+        let mut endings = endings.filter(|u| !u.is_empty() && *u != b"\x00");
+        endings.next().unwrap_or(b"\n")
+    }
+
+    /// Returns the file with all edits applied.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let ending = self.line_ending();
+        let mut buffer = self.bom.clone();
+
+        let mut lines = self.lines().peekable();
+        while let Some(line) = lines.next() {
+            line.write_to(&mut buffer, ending, lines.peek().is_none());
+        }
+
+        buffer
+    }
+}
+
+/// Splits the input into lines and their endings, i.e. `\r\n`, `\n`, `\r` or `\0`.
+fn split_lines(input: &[u8]) -> Vec<(&[u8], &[u8])> {
+    let mut lines = Vec::new();
+    let mut input = input;
+    while !input.is_empty() {
+        let end = input.iter().position(|u| b"\r\n\x00".contains(u));
+        let end = end.unwrap_or(input.len());
+        let len = match &input[end..] {
+            [b'\r', b'\n', ..] => 2,
+            [] => 0,
+            _ => 1,
+        };
+
+        lines.push((&input[..end], &input[end..end + len]));
+        input = &input[end + len..];
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod editing {
+    use super::*;
+    use crate::parse::Document;
+
+    static TXT: &[u8] = b"\xef\xbb\xbf# Robots\r\n\
+        Sitemap: https://example.com/a.xml\r\n\
+        \r\n\
+        # Foo and bar\r\n\
+        USER-AGENT : FooBot\r\n\
+        useragent barbot \r\n\
+        dissallow: /a  # legacy\x00\
+        Allow:/a/b\r\n\
+        \t\r\n\
+        user-agent: *\n\
+        Disallow:\r\n\
+        foo: bar";
+
+    #[test]
+    fn lossless() {
+        let tree = SyntaxTree::from_slice(TXT);
+        assert_eq!(tree.to_bytes(), TXT);
+        assert_eq!(tree.preamble().len(), 3);
+        assert_eq!(tree.groups().len(), 2);
+
+        let foo = &tree.groups()[0];
+        assert_eq!(foo.lines()[0].kind(), LineKind::Comment);
+        let uas: Vec<_> = foo.user_agents().collect();
+        assert_eq!(uas, vec![&b"FooBot"[..], b"barbot"]);
+        assert_eq!(foo.rules().count(), 2);
+
+        for u in [&b""[..], b"\n", b"\r\n\r\n", b"a\rb", b"\xef\xbb"] {
+            assert_eq!(SyntaxTree::from_slice(u).to_bytes(), u);
+        }
+    }
+
+    #[test]
+    fn rules() {
+        let mut tree = SyntaxTree::from_slice(TXT);
+        let group = tree.group_mut("foobot").unwrap();
+        assert!(group.remove_rule("/a/b", true));
+        assert!(!group.remove_rule("/a/b", false));
+        group.disallow("/c").allow("/c/d");
+        group.lines_mut()[3].set_value("/b");
+
+        let edited = b"\xef\xbb\xbf# Robots\r\n\
+            Sitemap: https://example.com/a.xml\r\n\
+            \r\n\
+            # Foo and bar\r\n\
+            USER-AGENT : FooBot\r\n\
+            useragent barbot \r\n\
+            dissallow: /b  # legacy\x00\
+            Disallow: /c\r\n\
+            Allow: /c/d\r\n\
+            \t\r\n\
+            user-agent: *\n\
+            Disallow:\r\n\
+            foo: bar";
+        assert_eq!(tree.to_bytes(), edited);
+
+        let d = Document::from_slice(&tree.to_bytes());
+        assert!(!d.robots("barbot").is_allowed("/b"));
+        assert!(d.robots("barbot").is_allowed("/c/d"));
+    }
+
+    #[test]
+    fn groups() {
+        let mut tree = SyntaxTree::from_slice(TXT);
+        assert_eq!(tree.remove_group("BarBot"), 1);
+        assert_eq!(tree.remove_group("barbot"), 0);
+
+        let mut group = SyntaxGroup::new(["nombot"]);
+        group.disallow("/");
+        tree.push_group(group);
+
+        let edited = b"\xef\xbb\xbf# Robots\r\n\
+            Sitemap: https://example.com/a.xml\r\n\
+            \r\n\
+            user-agent: *\n\
+            Disallow:\r\n\
+            foo: bar\r\n\
+            \r\n\
+            User-Agent: nombot\r\n\
+            Disallow: /\r\n";
+        assert_eq!(tree.to_bytes(), edited);
+
+        let d = Document::from_slice(&tree.to_bytes());
+        assert!(!d.robots("nombot").is_allowed("/a"));
+        assert!(d.robots("foobot").is_allowed("/a"));
+    }
+}
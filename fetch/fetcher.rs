@@ -0,0 +1,409 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use reqwest::header::{CACHE_CONTROL, CONTENT_TYPE, LOCATION, USER_AGENT};
+use reqwest::redirect::Policy;
+use reqwest::{Client, Error as HttpError, Response};
+use url::Url;
+
+use crate::fetch::FetchOptions;
+use crate::parse::{AccessResult, Document, Robots, StreamParser, ROBOTS_TXT};
+
+/// An error type indicating that the `robots.txt` file could not be fetched.
+/// NOTE: Network errors are not reported, as they mean that the file
+/// is unreachable, see [AccessResult::Unreachable].
+#[derive(Debug)]
+pub enum FetchError {
+    /// The URL has no host or its scheme is neither `http` nor `https`.
+    InvalidUrl,
+    /// The HTTP client could not be created.
+    Client(HttpError),
+}
+
+impl Display for FetchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self {
+            Self::InvalidUrl => write!(f, "url is not an http(s) url with a host"),
+            Self::Client(e) => write!(f, "http client could not be created: {e}"),
+        }
+    }
+}
+
+impl Error for FetchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self {
+            Self::InvalidUrl => None,
+            Self::Client(e) => Some(e),
+        }
+    }
+}
+
+/// Returns the URL of the `robots.txt` file of the URL origin.
+///
+/// ```rust
+/// use robotxt::robots_url;
+/// use url::Url;
+///
+/// let u = Url::parse("https://user@example.com:8080/a/b?c=d").unwrap();
+/// let u = robots_url(&u).unwrap();
+/// assert_eq!(u.as_str(), "https://example.com:8080/robots.txt");
+/// ```
+pub fn robots_url(url: &Url) -> Result<Url, FetchError> {
+    let origin = url.origin();
+    match url.scheme() {
+        "http" | "https" if origin.is_tuple() => {}
+        _ => return Err(FetchError::InvalidUrl),
+    };
+
+    let robots = origin.ascii_serialization() + ROBOTS_TXT;
+    Url::parse(robots.as_str()).map_err(|_| FetchError::InvalidUrl)
+}
+
+/// The `Fetched` struct represents the outcome of the
+/// `robots.txt` retrieval. See [Fetcher::fetch].
+#[derive(Debug, Clone)]
+pub struct Fetched {
+    robots: Robots,
+    url: Url,
+    status: Option<u16>,
+    redirects: usize,
+    cache_control: Option<String>,
+}
+
+impl Fetched {
+    /// Returns the `Robots` bound to the origin of the `robots.txt` file.
+    pub fn robots(&self) -> &Robots {
+        &self.robots
+    }
+
+    /// Returns the `Robots` bound to the origin of the `robots.txt` file.
+    pub fn into_robots(self) -> Robots {
+        self.robots
+    }
+
+    /// Returns the URL of the last request.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Returns the status code of the last response.
+    /// NOTE: `None` if the server could not be reached.
+    pub fn status(&self) -> Option<u16> {
+        self.status
+    }
+
+    /// Returns the number of followed redirect hops.
+    pub fn redirects(&self) -> usize {
+        self.redirects
+    }
+
+    /// Returns the `Cache-Control` header value of the last response (if any).
+    /// See [crate::RobotsCache::insert].
+    pub fn cache_control(&self) -> Option<&str> {
+        self.cache_control.as_deref()
+    }
+}
+
+/// The `Fetcher` struct provides the retrieval of the `robots.txt` file
+/// of the origin over HTTP with the redirect, status code and
+/// file size limit handling according to
+/// [Request for Comments: 9309](https://www.rfc-editor.org/rfc/rfc9309.html#section-2.3).
+///
+/// ```rust,no_run
+/// use robotxt::{FetchOptions, Fetcher};
+/// use url::Url;
+///
+/// # async fn fetch() -> Result<(), robotxt::FetchError> {
+/// let fetcher = Fetcher::new(FetchOptions::default())?;
+/// let url = Url::parse("https://example.com/example/").unwrap();
+/// let fetched = fetcher.fetch(&url, "foobot").await?;
+///
+/// let r = fetched.robots();
+/// println!("{:?} {}", fetched.status(), r.is_allowed("/example/"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Fetcher {
+    client: Client,
+    options: FetchOptions,
+}
+
+impl Fetcher {
+    /// Creates a new `Fetcher` with the specified options.
+    pub fn new(options: FetchOptions) -> Result<Self, FetchError> {
+        // Redirects are followed manually to enforce the limit.
+        let client = Client::builder()
+            .redirect(Policy::none())
+            .timeout(options.timeout)
+            .connect_timeout(options.connect_timeout)
+            .build()
+            .map_err(FetchError::Client)?;
+
+        Ok(Self { client, options })
+    }
+
+    /// Returns the options of the `Fetcher`.
+    pub fn options(&self) -> &FetchOptions {
+        &self.options
+    }
+
+    /// Fetches the `robots.txt` file of the URL origin and
+    /// creates the `Robots` for the user-agent from it.
+    /// NOTE: Fails only if the URL is invalid, see [robots_url].
+    pub async fn fetch(&self, url: &Url, user_agent: &str) -> Result<Fetched, FetchError> {
+        let robots_url = robots_url(url)?;
+        let mut fetched = Fetched {
+            robots: Robots::from_access(AccessResult::Unreachable, user_agent),
+            url: robots_url.clone(),
+            status: None,
+            redirects: 0,
+            cache_control: None,
+        };
+
+        let response = match self.follow(&mut fetched, user_agent).await {
+            Ok(response) => response,
+            Err(_) => {
+                fetched.robots = fetched.robots.with_origin(&robots_url);
+                return Ok(fetched);
+            }
+        };
+
+        let status = response.status().as_u16();
+        fetched.status = Some(status);
+        fetched.cache_control = response
+            .headers()
+            .get(CACHE_CONTROL)
+            .and_then(|u| u.to_str().ok())
+            .map(|u| u.to_string());
+
+        // The redirect limit was already enforced while following.
+        let robots = match AccessResult::from_status(status, 0, b"") {
+            AccessResult::Successful(_) => match self.read(response).await {
+                Ok(document) => Robots::from_document(&document, user_agent),
+                Err(_) => Robots::from_access(AccessResult::Unreachable, user_agent),
+            },
+            access => Robots::from_access(access, user_agent),
+        };
+
+        fetched.robots = robots.with_origin(&robots_url);
+        Ok(fetched)
+    }
+
+    /// Sends the request and follows the redirects until the limit.
+    /// Returns the last response.
+    async fn follow(&self, fetched: &mut Fetched, user_agent: &str) -> Result<Response, HttpError> {
+        loop {
+            let request = self.client.get(fetched.url.clone());
+            let response = request.header(USER_AGENT, user_agent).send().await?;
+
+            let location = response.headers().get(LOCATION);
+            let location = location.and_then(|u| u.to_str().ok());
+            let location = location.and_then(|u| fetched.url.join(u).ok());
+            let follows = response.status().is_redirection();
+            let follows = follows && fetched.redirects < self.options.redirect_limit;
+            match location {
+                Some(location) if follows => {
+                    fetched.redirects += 1;
+                    fetched.url = location;
+                }
+                _ => return Ok(response),
+            }
+        }
+    }
+
+    /// Parses the response body as it is received in the charset
+    /// of the `Content-Type` header (if any).
+    /// NOTE: Stops receiving the oversized body.
+    async fn read(&self, mut response: Response) -> Result<Document, HttpError> {
+        let content_type = response.headers().get(CONTENT_TYPE);
+        let content_type = content_type.and_then(|u| u.to_str().ok());
+        let options = self.options.parser.clone();
+        let options = match content_type {
+            Some(content_type) => options.content_type(content_type),
+            None => options,
+        };
+
+        let mut parser = StreamParser::new(&options);
+        while let Some(chunk) = response.chunk().await? {
+            if parser.push(&chunk).is_break() {
+                break;
+            }
+        }
+
+        Ok(parser.finish())
+    }
+}
+
+#[cfg(test)]
+mod fetching {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::parse::BYTES_LIMIT;
+
+    /// Starts the local HTTP server that responds with the handler output
+    /// to the request path. Returns the server URL.
+    fn serve<F>(handler: F) -> Url
+    where
+        F: Fn(&str) -> Vec<u8> + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(&stream);
+
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let path = line.split(' ').nth(1).unwrap_or("/").to_string();
+
+                // Skips the request headers.
+                while reader.read_line(&mut line).is_ok_and(|u| u > 2) {}
+                let _ = stream.write_all(&handler(&path));
+            }
+        });
+
+        Url::parse(&format!("http://{address}/example/")).unwrap()
+    }
+
+    fn response(status: &str, headers: &str, body: &[u8]) -> Vec<u8> {
+        let head = format!(
+            "HTTP/1.1 {status}\r\n{headers}Content-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        );
+
+        [head.as_bytes(), body].concat()
+    }
+
+    fn redirect(location: &str) -> Vec<u8> {
+        let location = format!("Location: {location}\r\n");
+        response("301 Moved Permanently", &location, b"")
+    }
+
+    /// Redirects from the `robots.txt` file through the number of hops.
+    fn hops(count: usize) -> impl Fn(&str) -> Vec<u8> {
+        move |path| match path {
+            "/robots.txt" => redirect("/0"),
+            _ => match path[1..].parse::<usize>().unwrap() + 1 {
+                hop if hop < count => redirect(&format!("/{hop}")),
+                _ => response("200 OK", "", b"User-Agent: *\nDisallow: /"),
+            },
+        }
+    }
+
+    fn fetch(url: &Url, options: FetchOptions) -> Fetched {
+        let fetcher = Fetcher::new(options).unwrap();
+        let mut runtime = tokio::runtime::Builder::new_current_thread();
+        let runtime = runtime.enable_all().build().unwrap();
+        runtime.block_on(fetcher.fetch(url, "foobot")).unwrap()
+    }
+
+    #[test]
+    fn url() {
+        let u = Url::parse("http://example.com").unwrap();
+        assert_eq!(
+            robots_url(&u).unwrap().as_str(),
+            "http://example.com/robots.txt"
+        );
+
+        for u in ["ftp://example.com/", "mailto:foo@example.com", "file:///a"] {
+            let u = Url::parse(u).unwrap();
+            assert!(matches!(robots_url(&u), Err(FetchError::InvalidUrl)));
+        }
+    }
+
+    #[test]
+    fn successful() {
+        let url = serve(|path| match path {
+            "/robots.txt" => {
+                let headers = "Cache-Control: max-age=60\r\n";
+                response("200 OK", headers, b"User-Agent: foobot\nDisallow: /a")
+            }
+            _ => response("404 Not Found", "", b""),
+        });
+
+        let f = fetch(&url, FetchOptions::default());
+        assert_eq!(f.status(), Some(200));
+        assert_eq!(f.redirects(), 0);
+        assert_eq!(f.cache_control(), Some("max-age=60"));
+        assert_eq!(f.robots().origin(), Some(&url.origin()));
+        assert!(!f.robots().is_allowed("/a"));
+        assert!(f.robots().is_allowed("/b"));
+    }
+
+    #[test]
+    fn redirects() {
+        let f = fetch(&serve(hops(5)), FetchOptions::default());
+        assert_eq!((f.status(), f.redirects()), (Some(200), 5));
+        assert_eq!(f.url().path(), "/4");
+        assert!(!f.robots().is_allowed("/a"));
+
+        // The limit is exceeded, the file is unavailable.
+        let f = fetch(&serve(hops(6)), FetchOptions::default());
+        assert_eq!((f.status(), f.redirects()), (Some(301), 5));
+        assert!(f.robots().is_allowed("/a"));
+
+        let options = FetchOptions::default().redirect_limit(6);
+        let f = fetch(&serve(hops(6)), options);
+        assert_eq!((f.status(), f.redirects()), (Some(200), 6));
+        assert!(!f.robots().is_allowed("/a"));
+    }
+
+    #[test]
+    fn statuses() {
+        let cases = [
+            ("404 Not Found", true),
+            ("403 Forbidden", true),
+            ("429 Too Many Requests", false),
+            ("503 Service Unavailable", false),
+        ];
+
+        for (status, allowed) in cases {
+            let url = serve(move |_| response(status, "", b"User-Agent: *\nAllow: /"));
+            let f = fetch(&url, FetchOptions::default());
+            assert_eq!(f.robots().is_always(), Some(allowed), "{status}");
+        }
+    }
+
+    #[test]
+    fn charset() {
+        let url = serve(|_| {
+            let headers = "Content-Type: text/plain; charset=windows-1251\r\n";
+            response("200 OK", headers, b"User-Agent: *\nDisallow: /\xf4\xf3")
+        });
+
+        let f = fetch(&url, FetchOptions::default());
+        assert!(!f.robots().is_allowed("/фу"));
+        assert!(f.robots().is_allowed("/a"));
+    }
+
+    #[test]
+    fn limit() {
+        let mut txt = b"User-Agent: *\n".to_vec();
+        txt.extend(b"Disallow: /a\n".repeat(BYTES_LIMIT / 10));
+        let url = serve(move |_| response("200 OK", "", &txt));
+
+        let f = fetch(&url, FetchOptions::default());
+        assert_eq!(f.robots().truncated(), Some(BYTES_LIMIT));
+        assert!(!f.robots().is_allowed("/a"));
+    }
+
+    #[test]
+    fn unreachable() {
+        let url = serve(|_| {
+            thread::sleep(Duration::from_millis(500));
+            response("200 OK", "", b"")
+        });
+
+        let options = FetchOptions::default().timeout(Duration::from_millis(100));
+        let f = fetch(&url, options);
+        assert_eq!(f.status(), None);
+        assert_eq!(f.robots().is_always(), Some(false));
+        assert_eq!(f.robots().origin(), Some(&url.origin()));
+    }
+}
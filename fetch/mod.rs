@@ -0,0 +1,5 @@
+mod fetcher;
+mod options;
+
+pub use fetcher::*;
+pub use options::*;
@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use crate::parse::{ParserOptions, REDIRECT_LIMIT};
+
+/// The `FetchOptions` struct represents the settings of the [crate::Fetcher].
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use robotxt::{FetchOptions, ParserOptions};
+///
+/// let options = FetchOptions::default()
+///     .timeout(Duration::from_secs(10))
+///     .connect_timeout(Duration::from_secs(2))
+///     .redirect_limit(3)
+///     .parser(ParserOptions::default().cut_at_line(true));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchOptions {
+    pub(crate) timeout: Duration,
+    pub(crate) connect_timeout: Duration,
+    pub(crate) redirect_limit: usize,
+    pub(crate) parser: ParserOptions,
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(10),
+            redirect_limit: REDIRECT_LIMIT,
+            parser: ParserOptions::default(),
+        }
+    }
+}
+
+impl FetchOptions {
+    /// Sets the time limit of every request, including reading the body.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the time limit of establishing the connection.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Sets the number of redirect hops to follow before the
+    /// `robots.txt` file is assumed to be unavailable.
+    /// See [REDIRECT_LIMIT].
+    pub fn redirect_limit(mut self, limit: usize) -> Self {
+        self.redirect_limit = limit;
+        self
+    }
+
+    /// Sets the options the fetched file is parsed with,
+    /// including the file size limit.
+    pub fn parser(mut self, options: ParserOptions) -> Self {
+        self.parser = options;
+        self
+    }
+}
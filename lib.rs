@@ -25,7 +25,20 @@
 //! - build the new `robots.txt` file from provided directives:
 //!
 //! ```rust
-//! use robotxt::Robots;
+//! use robotxt::RobotsBuilder;
+//!
+//! let txt = RobotsBuilder::default()
+//!     .header("Robots.txt: Start")
+//!     .group(["foobot"], |u| {
+//!         u.crawl_delay(std::time::Duration::from_secs(5))
+//!             .allow("/example/yeah.txt")
+//!             .disallow("/example/nope.txt")
+//!     })
+//!     .group(["barbot", "nombot"], |u| u.disallow("/"))
+//!     .sitemap("https://example.com/sitemap.xml".try_into().unwrap())
+//!     .footer("Robots.txt: End");
+//!
+//! println!("{}", txt.to_string());
 //! ```
 //!
 //! ## Links
@@ -47,5 +60,22 @@
 mod build;
 pub use build::*;
 
+mod cache;
+pub use cache::*;
+
+mod edit;
+pub use edit::*;
+
+#[cfg(feature = "fetch")]
+mod fetch;
+#[cfg(feature = "fetch")]
+pub use fetch::*;
+
+mod lint;
+pub use lint::*;
+
 mod parse;
 pub use parse::*;
+
+mod snapshot;
+pub use snapshot::*;
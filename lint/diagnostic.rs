@@ -0,0 +1,143 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::parse::Span;
+
+/// The `Severity` enum represents the importance of the `Diagnostic`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The directive is ignored by the parser.
+    Error,
+    /// The directive is accepted, but might be interpreted
+    /// differently by other parsers.
+    Warning,
+    /// The directive is accepted, but has no effect.
+    Notice,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self {
+            Self::Error => write!(f, "error"),
+            Self::Warning => write!(f, "warning"),
+            Self::Notice => write!(f, "notice"),
+        }
+    }
+}
+
+/// The `Code` enum represents every kind of the `Diagnostic`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Code {
+    /// The directive key is a known misspelling, e.g. `dissallow`.
+    Misspelled,
+    /// The directive is not recognized.
+    UnknownDirective,
+    /// The directive value is not valid UTF-8.
+    InvalidUtf8,
+    /// The transcoded input has malformed bytes that were replaced.
+    /// See [crate::ParserOptions::charset].
+    LossyDecoding,
+    /// The rule precedes any `user-agent` directive.
+    RuleOutsideGroup,
+    /// The group has no `allow` or `disallow` rules.
+    EmptyGroup,
+    /// The `allow` or `disallow` pattern can never match any path.
+    UnmatchablePattern,
+    /// The `crawl-delay` value is not a non-negative number.
+    InvalidCrawlDelay,
+    /// The `request-rate` value is not `n/m[s|m|h|d]`.
+    InvalidRequestRate,
+    /// The `visit-time` value is not `HHMM-HHMM`.
+    InvalidVisitTime,
+    /// The `sitemap` value is not an absolute URL.
+    InvalidSitemap,
+    /// The `clean-param` value is not `p0[&p1..] [path]`.
+    InvalidCleanParam,
+    /// The `host` value is not `[scheme://]host[:port]`.
+    InvalidHost,
+    /// The file exceeds the size limit and is truncated.
+    Truncated,
+}
+
+impl Code {
+    /// Returns the kebab-case name of the code.
+    pub fn as_str(&self) -> &'static str {
+        match &self {
+            Self::Misspelled => "misspelled",
+            Self::UnknownDirective => "unknown-directive",
+            Self::InvalidUtf8 => "invalid-utf8",
+            Self::LossyDecoding => "lossy-decoding",
+            Self::RuleOutsideGroup => "rule-outside-group",
+            Self::EmptyGroup => "empty-group",
+            Self::UnmatchablePattern => "unmatchable-pattern",
+            Self::InvalidCrawlDelay => "invalid-crawl-delay",
+            Self::InvalidRequestRate => "invalid-request-rate",
+            Self::InvalidVisitTime => "invalid-visit-time",
+            Self::InvalidSitemap => "invalid-sitemap",
+            Self::InvalidCleanParam => "invalid-clean-param",
+            Self::InvalidHost => "invalid-host",
+            Self::Truncated => "truncated",
+        }
+    }
+}
+
+impl Display for Code {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The `Diagnostic` struct represents a single problem found
+/// in the `robots.txt` file. See [crate::lint].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    severity: Severity,
+    code: Code,
+    line: usize,
+    span: Span,
+    message: String,
+}
+
+impl Diagnostic {
+    /// Creates a new `Diagnostic` with the specified location and message.
+    pub fn new(severity: Severity, code: Code, line: usize, span: Span, message: String) -> Self {
+        Self {
+            severity,
+            code,
+            line,
+            span,
+            message,
+        }
+    }
+
+    /// Returns the severity of the diagnostic.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Returns the code of the diagnostic.
+    pub fn code(&self) -> Code {
+        self.code
+    }
+
+    /// Returns the one-based line number.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the span of the problem in the original input.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Returns the human-readable message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let (severity, code, line) = (self.severity, self.code, self.line);
+        write!(f, "{severity}[{code}] line {line}: {}", self.message)
+    }
+}
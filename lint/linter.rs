@@ -0,0 +1,387 @@
+use bstr::ByteSlice;
+
+use crate::lint::{Code, Diagnostic, Severity};
+use crate::parse::{
+    decode, into_directives_with, normalize_path, Directive, ParserOptions, Position, Span,
+};
+use crate::parse::{parse_clean_param, parse_crawl_delay, parse_host, parse_sitemap, Wildcard};
+use crate::parse::{parse_request_rate, parse_visit_time};
+
+/// The canonical spellings of all supported directives.
+const CANONICAL: &[&str] = &[
+    "user-agent",
+    "allow",
+    "disallow",
+    "crawl-delay",
+    "request-rate",
+    "visit-time",
+    "sitemap",
+    "clean-param",
+    "host",
+];
+
+/// The `Group` struct represents the state of the currently linted group.
+struct Group {
+    line: usize,
+    span: Span,
+    rules: usize,
+}
+
+/// The `Linter` struct accumulates diagnostics of the single file.
+#[derive(Default)]
+struct Linter {
+    diagnostics: Vec<Diagnostic>,
+    group: Option<Group>,
+    captures_group: bool,
+}
+
+impl Linter {
+    /// Adds the new diagnostic at the specified position.
+    fn report(&mut self, severity: Severity, code: Code, position: Position, message: String) {
+        let span = match position.value().is_empty() {
+            true => position.key(),
+            false => position.value(),
+        };
+
+        let diagnostic = Diagnostic::new(severity, code, position.line(), span, message);
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// Reports the current group if it has no rules.
+    fn close_group(&mut self) {
+        if let Some(group) = self.group.take() {
+            if group.rules == 0 {
+                let message = "group has no allow or disallow rules".to_string();
+                let (severity, code) = (Severity::Warning, Code::EmptyGroup);
+                let diagnostic = Diagnostic::new(severity, code, group.line, group.span, message);
+                self.diagnostics.push(diagnostic);
+            }
+        }
+    }
+
+    /// Reports the unknown directive unless it is blank or a comment.
+    fn unknown(&mut self, line: &[u8], position: Position) {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(b"#") {
+            return;
+        }
+
+        let key = line.split_str(":").next().unwrap_or_default();
+        let key = String::from_utf8_lossy(key.trim());
+        let message = format!("unknown directive `{key}` is ignored");
+        self.report(Severity::Notice, Code::UnknownDirective, position, message);
+    }
+
+    /// Reports the directive key if it is not spelled canonically.
+    fn spelling(&mut self, directive: &Directive, key: &[u8], position: Position) {
+        let key = String::from_utf8_lossy(key).to_lowercase();
+        if CANONICAL.contains(&key.as_str()) {
+            return;
+        }
+
+        let canonical = match directive {
+            Directive::UserAgent(_) => "user-agent",
+            // Empty disallow is lexed as allow.
+            Directive::Allow(_) if key.starts_with('d') => "disallow",
+            Directive::Allow(_) => "allow",
+            Directive::Disallow(_) => "disallow",
+            Directive::CrawlDelay(_) => "crawl-delay",
+            Directive::RequestRate(_) => "request-rate",
+            Directive::VisitTime(_) => "visit-time",
+            Directive::Sitemap(_) => "sitemap",
+            Directive::CleanParam(_) => "clean-param",
+            Directive::Host(_) => "host",
+            Directive::Unknown(_) => return,
+        };
+
+        let message = format!("`{key}` is a misspelling of `{canonical}`");
+        self.report(Severity::Warning, Code::Misspelled, position, message);
+    }
+
+    /// Reports the problems of the single directive.
+    fn directive(&mut self, directive: &Directive, key: &[u8], position: Position) {
+        let value = match directive {
+            Directive::Unknown(u) => return self.unknown(u, position),
+            Directive::UserAgent(u) | Directive::Allow(u) | Directive::Disallow(u) => u,
+            Directive::CrawlDelay(u) | Directive::Sitemap(u) => u,
+            Directive::RequestRate(u) | Directive::VisitTime(u) => u,
+            Directive::CleanParam(u) | Directive::Host(u) => u,
+        };
+
+        self.spelling(directive, key, position);
+        if value.to_str().is_err() {
+            let message = "value is not valid UTF-8, directive is ignored".to_string();
+            self.report(Severity::Error, Code::InvalidUtf8, position, message);
+        }
+
+        match directive {
+            Directive::UserAgent(_) => {
+                if !self.captures_group {
+                    self.close_group();
+                    let (line, span) = (position.line(), position.key());
+                    self.group = Some(Group {
+                        line,
+                        span,
+                        rules: 0,
+                    });
+                }
+
+                self.captures_group = true;
+                return;
+            }
+
+            Directive::Sitemap(u) => {
+                if u.to_str().is_ok() && parse_sitemap(u).is_none() {
+                    let message = "sitemap is not an absolute URL, ignored".to_string();
+                    self.report(Severity::Error, Code::InvalidSitemap, position, message);
+                }
+
+                return;
+            }
+
+            Directive::CleanParam(u) => {
+                if u.to_str().is_ok() && parse_clean_param(u).is_none() {
+                    let message = "clean-param is not `p0[&p1..] [path]`, ignored".to_string();
+                    self.report(Severity::Error, Code::InvalidCleanParam, position, message);
+                }
+
+                return;
+            }
+
+            Directive::Host(u) => {
+                if u.to_str().is_ok() && parse_host(u).is_none() {
+                    let message = "host is not `[scheme://]host[:port]`, ignored".to_string();
+                    self.report(Severity::Error, Code::InvalidHost, position, message);
+                }
+
+                return;
+            }
+
+            _ => self.captures_group = false,
+        }
+
+        match &mut self.group {
+            Some(group) if matches!(directive, Directive::Allow(_) | Directive::Disallow(_)) => {
+                group.rules += 1
+            }
+
+            Some(_) => {}
+            None => {
+                let message = "rule precedes any user-agent".to_string();
+                self.report(Severity::Warning, Code::RuleOutsideGroup, position, message);
+            }
+        }
+
+        match directive {
+            Directive::Allow(u) | Directive::Disallow(u) if u.to_str().is_ok() => {
+                self.pattern(u, position);
+            }
+
+            Directive::CrawlDelay(u) if u.to_str().is_ok() && parse_crawl_delay(u).is_none() => {
+                let message = "crawl-delay is not a non-negative number, ignored".to_string();
+                self.report(Severity::Error, Code::InvalidCrawlDelay, position, message);
+            }
+
+            Directive::RequestRate(u) if u.to_str().is_ok() && parse_request_rate(u).is_none() => {
+                let message = "request-rate is not `n/m[s|m|h|d]`, ignored".to_string();
+                self.report(Severity::Error, Code::InvalidRequestRate, position, message);
+            }
+
+            Directive::VisitTime(u) if u.to_str().is_ok() && parse_visit_time(u).is_none() => {
+                let message = "visit-time is not `HHMM-HHMM`, ignored".to_string();
+                self.report(Severity::Error, Code::InvalidVisitTime, position, message);
+            }
+
+            _ => {}
+        }
+    }
+
+    /// Reports the pattern if it can never match.
+    fn pattern(&mut self, pattern: &[u8], position: Position) {
+        // The '$' character only anchors the end of the pattern.
+        let pattern = normalize_path(&String::from_utf8_lossy(pattern));
+        if matches!(Wildcard::new(&pattern), Some(Wildcard::Never)) {
+            let message = format!("pattern `{pattern}` can never match, `$` is not at the end");
+            self.report(
+                Severity::Warning,
+                Code::UnmatchablePattern,
+                position,
+                message,
+            );
+        }
+    }
+}
+
+/// Checks the `robots.txt` file and returns all found problems
+/// ordered by line.
+///
+/// ```rust
+/// use robotxt::{lint, Code};
+///
+/// let txt = b"Disallow: /a\nUser-Agent: foobot\nDissallow: /b\nSitemap: /sitemap.xml";
+/// let codes: Vec<_> = lint(txt).iter().map(|d| d.code()).collect();
+/// assert_eq!(codes, vec![Code::RuleOutsideGroup, Code::Misspelled, Code::InvalidSitemap]);
+/// ```
+pub fn lint(robots: &[u8]) -> Vec<Diagnostic> {
+    lint_with(robots, &ParserOptions::default())
+}
+
+/// Checks the `robots.txt` file with the specified options and
+/// returns all found problems ordered by line.
+pub fn lint_with(robots: &[u8], options: &ParserOptions) -> Vec<Diagnostic> {
+    let (robots, replaced) = decode(robots, options.charset, options.limit);
+    let (prepared, truncated) = options.prepare(&robots);
+    let tokens = into_directives_with(prepared, options.dialect);
+
+    let mut linter = Linter::default();
+    for token in &tokens {
+        let position = token.position();
+        let key = &prepared[position.key().range()];
+        linter.directive(token.directive(), key, position);
+    }
+
+    linter.close_group();
+    let mut diagnostics = linter.diagnostics;
+
+    // Reports the first replaced sequence of the transcoded input.
+    if let Some(start) = replaced.filter(|u| *u < prepared.len()) {
+        let token = tokens.iter().rfind(|t| t.position().offset() <= start);
+        let line = token.map(|t| t.position().line()).unwrap_or(1);
+        let span = Span::new(start, start + '\u{fffd}'.len_utf8());
+        let message = "malformed bytes were replaced while decoding".to_string();
+        let (severity, code) = (Severity::Warning, Code::LossyDecoding);
+        let diagnostic = Diagnostic::new(severity, code, line, span, message);
+        diagnostics.push(diagnostic);
+    }
+
+    diagnostics.sort_by_key(|d| d.line());
+
+    if let Some(truncated) = truncated {
+        let line = tokens.last().map(|t| t.position().line()).unwrap_or(1);
+        // NOTE: The transcoded input is decoded only up to the limit.
+        let span = Span::new(truncated, robots.len());
+        let message = format!("file exceeds {} bytes, the rest is ignored", options.limit);
+        let diagnostic = Diagnostic::new(Severity::Error, Code::Truncated, line, span, message);
+        diagnostics.push(diagnostic);
+    }
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod checking {
+    use super::*;
+    use crate::parse::BYTES_LIMIT;
+
+    fn codes(robots: &[u8]) -> Vec<(usize, Code)> {
+        let r = lint(robots).into_iter();
+        r.map(|d| (d.line(), d.code())).collect()
+    }
+
+    #[test]
+    fn clean() {
+        let r = b"# comment\nUser-Agent: foobot\nAllow: /a\n\nDisallow: /*.php$\n\
+            Crawl-Delay: 1.5\nSitemap: https://example.com/sitemap.xml\n";
+        assert_eq!(codes(r), vec![]);
+    }
+
+    #[test]
+    fn spelling() {
+        let r = b"Useragent: foobot\nDissallow: /a\nDissallow:\nALLOW: /b\nfoo: bar";
+        let r = codes(r);
+        assert_eq!(
+            r,
+            vec![
+                (1, Code::Misspelled),
+                (2, Code::Misspelled),
+                (3, Code::Misspelled),
+                (5, Code::UnknownDirective)
+            ]
+        );
+
+        let r = lint(b"User-Agent: *\nDissallow:");
+        assert_eq!(r[0].message(), "`dissallow` is a misspelling of `disallow`");
+    }
+
+    #[test]
+    fn groups() {
+        let r = b"Allow: /\nUser-Agent: foobot\nCrawl-Delay: 1\nUser-Agent: barbot\n\
+            User-Agent: nombot\nDisallow: /\nUser-Agent: *";
+        let r = codes(r);
+        assert_eq!(
+            r,
+            vec![
+                (1, Code::RuleOutsideGroup),
+                (2, Code::EmptyGroup),
+                (7, Code::EmptyGroup)
+            ]
+        );
+    }
+
+    #[test]
+    fn values() {
+        let r = b"User-Agent: *\nDisallow: /\xff\nDisallow: /a$b\n\
+            Crawl-Delay: -1\nSitemap: sitemap.xml\nClean-Param: a&&b\nHost: a/b\n\
+            Request-Rate: 1/0s\nVisit-Time: 2500-0100";
+        let r = codes(r);
+        assert_eq!(
+            r,
+            vec![
+                (2, Code::InvalidUtf8),
+                (3, Code::UnmatchablePattern),
+                (4, Code::InvalidCrawlDelay),
+                (5, Code::InvalidSitemap),
+                (6, Code::InvalidCleanParam),
+                (7, Code::InvalidHost),
+                (8, Code::InvalidRequestRate),
+                (9, Code::InvalidVisitTime)
+            ]
+        );
+    }
+
+    #[test]
+    fn lossy() {
+        // The unpaired surrogate at the end of the UTF-16 input.
+        let r = "\u{feff}User-Agent: *\nDisallow: /".encode_utf16();
+        let r: Vec<_> = r.chain([0xdc00]).flat_map(|u| u.to_le_bytes()).collect();
+        let d = lint(&r);
+        assert_eq!(d.len(), 1);
+        assert_eq!((d[0].line(), d[0].code()), (2, Code::LossyDecoding));
+        assert_eq!(d[0].span(), Span::new(25, 28));
+
+        // The incomplete code unit at the end of the UTF-16 input.
+        let r = "User-Agent: *\nDisallow: /".encode_utf16();
+        let r: Vec<_> = r.flat_map(|u| u.to_le_bytes()).chain([0x41]).collect();
+        let options = ParserOptions::default().content_type("text/plain; charset=utf-16le");
+        let d = lint_with(&r, &options);
+        assert_eq!(
+            d.iter().map(|d| d.code()).collect::<Vec<_>>(),
+            vec![Code::LossyDecoding]
+        );
+
+        // The replacement character of the input is not reported.
+        let r = "\u{feff}Disallow: /\u{fffd}\nDisallow: /".encode_utf16();
+        let r: Vec<_> = r.chain([0xdc00]).flat_map(|u| u.to_le_bytes()).collect();
+        let d: Vec<_> = lint(&r)
+            .into_iter()
+            .filter(|d| d.code() == Code::LossyDecoding)
+            .collect();
+        assert_eq!(d.len(), 1);
+        assert_eq!((d[0].line(), d[0].span()), (2, Span::new(26, 29)));
+    }
+
+    #[test]
+    fn truncated() {
+        let mut r = b"User-Agent: *\n".to_vec();
+        r.extend(b"Disallow: /a\n".repeat(BYTES_LIMIT / 10));
+
+        // The last line is cut in the middle of the directive.
+        let d = lint(r.as_slice());
+        let d: Vec<_> = d.iter().map(|d| (d.code(), d.span().start())).collect();
+        assert_eq!(d[1], (Code::Truncated, BYTES_LIMIT));
+
+        let options = ParserOptions::default().cut_at_line(true);
+        let d = lint_with(r.as_slice(), &options);
+        assert_eq!(d.len(), 1);
+    }
+}
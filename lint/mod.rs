@@ -0,0 +1,5 @@
+mod diagnostic;
+mod linter;
+
+pub use diagnostic::*;
+pub use linter::*;
@@ -0,0 +1,140 @@
+use std::io::ErrorKind;
+
+/// The number of redirect hops crawlers should follow before
+/// assuming that the `robots.txt` file is unavailable.
+/// See [Request for Comments: 9309](https://www.rfc-editor.org/rfc/rfc9309.html#section-2.3.1.2).
+pub const REDIRECT_LIMIT: usize = 5;
+
+/// The `AccessResult` enum represents the result of the
+/// `robots.txt` retrieval attempt. See [crate::Robots::from_access].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessResult<'a> {
+    /// The `robots.txt` file was provided by the server and
+    /// ready to be parsed.
+    Successful(&'a [u8]),
+    /// The `robots.txt` file has not been reached after
+    /// at least five redirect hops. Treated as `Unavailable`.
+    /// See [REDIRECT_LIMIT].
+    Redirect,
+    /// The valid `robots.txt` file does not exist.
+    /// The `Robots` assumes that there are no restrictions.
+    /// The site is fully allowed.
+    Unavailable,
+    /// The `robots.txt` file could not be served.
+    /// The site is fully disallowed.
+    Unreachable,
+}
+
+impl<'a> AccessResult<'a> {
+    /// Creates a new `AccessResult` from the status code of the final response,
+    /// the number of followed redirect hops and the response body.
+    /// NOTE: Redirects are expected to be followed by the caller.
+    ///
+    /// ```rust
+    /// use robotxt::AccessResult;
+    ///
+    /// let txt = b"User-Agent: *\nDisallow: /";
+    /// assert_eq!(AccessResult::from_status(200, 0, txt), AccessResult::Successful(txt));
+    /// assert_eq!(AccessResult::from_status(200, 6, txt), AccessResult::Redirect);
+    /// assert_eq!(AccessResult::from_status(404, 0, b""), AccessResult::Unavailable);
+    /// assert_eq!(AccessResult::from_status(503, 0, b""), AccessResult::Unreachable);
+    /// ```
+    pub fn from_status(status: u16, redirects: usize, body: &'a [u8]) -> Self {
+        match status {
+            // Crawlers may stop following after five redirect hops.
+            _ if redirects > REDIRECT_LIMIT => Self::Redirect,
+            200..=299 => Self::Successful(body),
+            // Redirects that were not followed until the end.
+            300..=399 => Self::Redirect,
+            // Too Many Requests is treated as a server error.
+            // See [How Google interprets Robots.txt](https://t.ly/uWvd).
+            429 => Self::Unreachable,
+            400..=499 => Self::Unavailable,
+            // Server errors and unexpected status codes.
+            _ => Self::Unreachable,
+        }
+    }
+
+    /// Creates a new `AccessResult` from the kind of the retrieval error.
+    /// Any network error (DNS, refused connection, timeout, etc.) means
+    /// the `robots.txt` file could not be served.
+    /// NOTE: The missing or forbidden file, e.g. of the local mirror,
+    /// is treated as `404` or `403` respectively.
+    ///
+    /// ```rust
+    /// use std::io::ErrorKind;
+    ///
+    /// use robotxt::AccessResult;
+    ///
+    /// assert_eq!(AccessResult::from_error(ErrorKind::NotFound), AccessResult::Unavailable);
+    /// assert_eq!(AccessResult::from_error(ErrorKind::TimedOut), AccessResult::Unreachable);
+    /// ```
+    pub fn from_error(kind: ErrorKind) -> Self {
+        match kind {
+            ErrorKind::NotFound | ErrorKind::PermissionDenied => Self::Unavailable,
+            _ => Self::Unreachable,
+        }
+    }
+}
+
+#[cfg(test)]
+mod statuses {
+    use super::*;
+
+    #[test]
+    fn successful() {
+        let r = AccessResult::from_status(204, 0, b"");
+        assert_eq!(r, AccessResult::Successful(b""));
+
+        let r = AccessResult::from_status(200, REDIRECT_LIMIT, b"txt");
+        assert_eq!(r, AccessResult::Successful(b"txt"));
+    }
+
+    #[test]
+    fn redirect() {
+        let r = AccessResult::from_status(301, 1, b"");
+        assert_eq!(r, AccessResult::Redirect);
+
+        let r = AccessResult::from_status(404, REDIRECT_LIMIT + 1, b"");
+        assert_eq!(r, AccessResult::Redirect);
+    }
+
+    #[test]
+    fn unavailable() {
+        for status in [400, 401, 403, 404, 410, 451] {
+            let r = AccessResult::from_status(status, 0, b"");
+            assert_eq!(r, AccessResult::Unavailable);
+        }
+
+        for kind in [ErrorKind::NotFound, ErrorKind::PermissionDenied] {
+            let r = AccessResult::from_error(kind);
+            assert_eq!(r, AccessResult::Unavailable, "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn unreachable() {
+        for status in [100, 429, 500, 502, 503, 599, 600] {
+            let r = AccessResult::from_status(status, 0, b"");
+            assert_eq!(r, AccessResult::Unreachable);
+        }
+
+        let kinds = [
+            ErrorKind::TimedOut,
+            ErrorKind::ConnectionRefused,
+            ErrorKind::ConnectionReset,
+            ErrorKind::ConnectionAborted,
+            ErrorKind::NotConnected,
+            ErrorKind::AddrNotAvailable,
+            ErrorKind::BrokenPipe,
+            ErrorKind::UnexpectedEof,
+            ErrorKind::InvalidData,
+            ErrorKind::Other,
+        ];
+
+        for kind in kinds {
+            let r = AccessResult::from_error(kind);
+            assert_eq!(r, AccessResult::Unreachable, "{kind:?}");
+        }
+    }
+}
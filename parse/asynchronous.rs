@@ -0,0 +1,284 @@
+use std::future::poll_fn;
+use std::io::{Error as IoError, ErrorKind};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::parse::{Document, ParserOptions, Robots, StreamParser, CHUNK_SIZE};
+
+/// The `Tokio` marker selects the [tokio::io::AsyncRead] readers.
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone, Copy)]
+pub enum Tokio {}
+
+/// The `FuturesIo` marker selects the [futures_io::AsyncRead] readers.
+#[cfg(feature = "futures-io")]
+#[derive(Debug, Clone, Copy)]
+pub enum FuturesIo {}
+
+/// The `AsyncSource` trait represents the async reader of the supported
+/// ecosystem identified by the marker, i.e. [Tokio] or [FuturesIo].
+///
+/// NOTE: The marker is inferred unless the reader implements
+/// both `AsyncRead` traits, e.g. `&[u8]` with both features enabled.
+pub trait AsyncSource<M>: Unpin {
+    /// Attempts to read into the buffer, returns the number of bytes read.
+    fn poll_read_chunk(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buffer: &mut [u8],
+    ) -> Poll<Result<usize, IoError>>;
+}
+
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncRead + Unpin> AsyncSource<Tokio> for R {
+    fn poll_read_chunk(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buffer: &mut [u8],
+    ) -> Poll<Result<usize, IoError>> {
+        let mut buffer = tokio::io::ReadBuf::new(buffer);
+        match tokio::io::AsyncRead::poll_read(self, cx, &mut buffer) {
+            Poll::Ready(Ok(())) => Poll::Ready(Ok(buffer.filled().len())),
+            Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl<R: futures_io::AsyncRead + Unpin> AsyncSource<FuturesIo> for R {
+    fn poll_read_chunk(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buffer: &mut [u8],
+    ) -> Poll<Result<usize, IoError>> {
+        futures_io::AsyncRead::poll_read(self, cx, buffer)
+    }
+}
+
+/// Parses the `Document` from the async reader as it is read.
+/// See [Document::from_reader_with].
+async fn read_document<M, R>(reader: R, options: &ParserOptions) -> Result<Document, IoError>
+where
+    R: AsyncSource<M>,
+{
+    let mut reader = reader;
+    let mut parser = StreamParser::new(options);
+
+    let mut chunk = vec![0; CHUNK_SIZE];
+    loop {
+        let read = poll_fn(|cx| Pin::new(&mut reader).poll_read_chunk(cx, &mut chunk));
+        let read = match read.await {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+
+        // Stops reading the oversized input.
+        if parser.push(&chunk[..read]).is_break() {
+            break;
+        }
+    }
+
+    Ok(parser.finish())
+}
+
+impl Document {
+    /// Creates a new `Document` from the async reader.
+    /// See [AsyncSource].
+    pub async fn from_async_reader<M, R>(reader: R) -> Result<Self, IoError>
+    where
+        R: AsyncSource<M>,
+    {
+        read_document(reader, &ParserOptions::default()).await
+    }
+
+    /// Creates a new `Document` from the async reader with the specified options.
+    /// See [AsyncSource].
+    pub async fn from_async_reader_with<M, R>(
+        reader: R,
+        options: &ParserOptions,
+    ) -> Result<Self, IoError>
+    where
+        R: AsyncSource<M>,
+    {
+        read_document(reader, options).await
+    }
+}
+
+impl Robots {
+    /// Creates a new `Robots` from the async reader.
+    /// See [AsyncSource].
+    pub async fn from_async_reader<M, R>(reader: R, user_agent: &str) -> Result<Self, IoError>
+    where
+        R: AsyncSource<M>,
+    {
+        let document = Document::from_async_reader(reader).await?;
+        Ok(Self::from_document(&document, user_agent))
+    }
+
+    /// Creates a new `Robots` from the async reader with the specified options.
+    /// See [AsyncSource].
+    pub async fn from_async_reader_with<M, R>(
+        reader: R,
+        user_agent: &str,
+        options: &ParserOptions,
+    ) -> Result<Self, IoError>
+    where
+        R: AsyncSource<M>,
+    {
+        let document = Document::from_async_reader_with(reader, options).await?;
+        Ok(Self::from_document(&document, user_agent))
+    }
+}
+
+#[cfg(test)]
+mod reading {
+    use std::future::Future;
+    use std::task::Waker;
+
+    use super::*;
+    use crate::parse::BYTES_LIMIT;
+
+    /// The `Chunks` struct represents the in-memory reader that returns
+    /// the input in small chunks and is pending before every one of them.
+    struct Chunks {
+        input: Vec<u8>,
+        position: usize,
+        pending: bool,
+    }
+
+    impl Chunks {
+        fn new(input: &[u8]) -> Self {
+            Self {
+                input: input.to_vec(),
+                position: 0,
+                pending: true,
+            }
+        }
+
+        fn poll(
+            &mut self,
+            cx: &mut Context<'_>,
+            buffer: &mut [u8],
+        ) -> Poll<Result<usize, IoError>> {
+            self.pending = !self.pending;
+            if !self.pending {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+
+            let end = (self.position + 7).min(self.input.len());
+            let end = end.min(self.position + buffer.len());
+            let chunk = &self.input[self.position..end];
+            buffer[..chunk.len()].copy_from_slice(chunk);
+            self.position = end;
+            Poll::Ready(Ok(chunk.len()))
+        }
+    }
+
+    /// Polls the future until it is ready.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    static TXT: &[u8] = b"User-Agent: foobot\r\nDisallow: /a\x00Allow: /a/b\nCrawl-Delay: 2";
+
+    fn oversized() -> Vec<u8> {
+        let mut txt = b"User-Agent: *\n".to_vec();
+        txt.extend(b"Disallow: /a\n".repeat(BYTES_LIMIT / 10));
+        txt
+    }
+
+    /// Compares the async parsed `Robots` with the one parsed from the slice.
+    fn compare(txt: &[u8], r: Robots) {
+        let expected = Robots::from_slice(txt, "foobot");
+        assert_eq!(r.truncated(), expected.truncated());
+        assert_eq!(r.crawl_delay(), expected.crawl_delay());
+        assert_eq!(r.rules().len(), expected.rules().len());
+        for path in ["/a", "/a/b", "/b"] {
+            assert_eq!(r.is_allowed(path), expected.is_allowed(path), "{path}");
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    mod tokio_reader {
+        use super::*;
+
+        struct TokioChunks(Chunks);
+
+        impl tokio::io::AsyncRead for TokioChunks {
+            fn poll_read(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut tokio::io::ReadBuf<'_>,
+            ) -> Poll<std::io::Result<()>> {
+                let buffer = buf.initialize_unfilled();
+                let read = std::task::ready!(self.0.poll(cx, buffer))?;
+                buf.advance(read);
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        #[test]
+        fn equivalence() {
+            for txt in [TXT.to_vec(), oversized()] {
+                let reader = TokioChunks(Chunks::new(&txt));
+                let r = block_on(Robots::from_async_reader(reader, "foobot"));
+                compare(&txt, r.unwrap());
+            }
+        }
+
+        #[test]
+        fn slice() {
+            // The marker is explicit as slices implement both traits.
+            let r = Robots::from_async_reader::<Tokio, _>(TXT, "foobot");
+            compare(TXT, block_on(r).unwrap());
+        }
+    }
+
+    #[cfg(feature = "futures-io")]
+    mod futures_reader {
+        use super::*;
+
+        struct FuturesChunks(Chunks);
+
+        impl futures_io::AsyncRead for FuturesChunks {
+            fn poll_read(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut [u8],
+            ) -> Poll<std::io::Result<usize>> {
+                self.0.poll(cx, buf)
+            }
+        }
+
+        #[test]
+        fn equivalence() {
+            for txt in [TXT.to_vec(), oversized()] {
+                let reader = FuturesChunks(Chunks::new(&txt));
+                let r = block_on(Robots::from_async_reader(reader, "foobot"));
+                compare(&txt, r.unwrap());
+            }
+        }
+
+        #[test]
+        fn options() {
+            let txt = oversized();
+            let options = ParserOptions::default().cut_at_line(true);
+            let reader = FuturesChunks(Chunks::new(&txt));
+            let d = block_on(Document::from_async_reader_with(reader, &options)).unwrap();
+
+            let expected = Document::from_slice_with(&txt, &options);
+            assert_eq!(d.truncated(), expected.truncated());
+            assert!(d.truncated().is_some());
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use std::borrow::Cow;
+use std::cmp::{min, Ordering};
+use std::str::from_utf8;
+use std::time::Duration;
+
+use crate::parse::{into_directives_with, is_normalized, match_pattern, normalize_path};
+use crate::parse::{parse_crawl_delay, Dialect, Directive, ParserOptions, DEFAULT, ROBOTS_TXT};
+
+/// Returns true if both user-agents are equal ignoring the case.
+fn same_agent(lhs: &str, rhs: &str) -> bool {
+    let lhs = lhs.chars().flat_map(char::to_lowercase);
+    lhs.eq(rhs.chars().flat_map(char::to_lowercase))
+}
+
+/// The `RuleRef` struct represents the `allow` or `disallow` rule
+/// that borrows its pattern from the input if it is already normalized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleRef<'a> {
+    pattern: Cow<'a, str>,
+    allow: bool,
+}
+
+impl<'a> RuleRef<'a> {
+    /// Creates a new `RuleRef` with the specified pattern and permission.
+    /// NOTE: Allocates only if the pattern has to be normalized.
+    pub fn new(pattern: &'a str, allow: bool) -> Self {
+        let pattern = match is_normalized(pattern) {
+            true => Cow::Borrowed(pattern),
+            false => Cow::Owned(normalize_path(pattern)),
+        };
+
+        Self { pattern, allow }
+    }
+
+    /// Returns true if the normalized relative path matches the pattern.
+    /// NOTE: Expects normalized relative path.
+    pub fn is_match(&self, path: &str) -> bool {
+        match_pattern(&self.pattern, path)
+    }
+
+    /// Returns true if allowed.
+    pub fn is_allowed(&self) -> bool {
+        self.allow
+    }
+
+    /// Returns the normalized pattern.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns true if the pattern is borrowed from the input.
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.pattern, Cow::Borrowed(_))
+    }
+}
+
+impl PartialOrd<Self> for RuleRef<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RuleRef<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match other.pattern.len().cmp(&self.pattern.len()) {
+            Ordering::Equal => other.allow.cmp(&self.allow),
+            v => v,
+        }
+    }
+}
+
+/// The `RobotsRef` struct represents the rules related to the specific
+/// `user-agent` borrowed from the input slice, i.e. the lightweight
+/// alternative to [crate::Robots] for the one-off checks.
+///
+/// NOTE: Only the `allow`, `disallow`, `crawl-delay` and `sitemap`
+/// directives are supported, use [crate::Robots] for the rest
+/// and for the input that is not UTF-8, see [ParserOptions::charset].
+///
+/// ```rust
+/// use robotxt::RobotsRef;
+///
+/// let txt = b"User-Agent: foobot\x00Disallow: /example/\nSitemap: https://example.com/a.xml";
+/// let r = RobotsRef::from_slice(txt, "FooBot/1.0");
+///
+/// assert_eq!(r.user_agent(), "foobot");
+/// assert!(!r.is_allowed("/example/nope.txt"));
+/// assert!(r.is_allowed("/example.txt"));
+/// assert!(r.rules().iter().all(|u| u.is_borrowed()));
+/// assert_eq!(r.sitemaps(), &["https://example.com/a.xml"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RobotsRef<'a> {
+    user_agent: &'a str,
+    rules: Vec<RuleRef<'a>>,
+    delay: Option<Duration>,
+    sitemaps: Vec<&'a str>,
+    truncated: Option<usize>,
+}
+
+impl<'a> RobotsRef<'a> {
+    /// Finds the longest matching user-agent without allocating.
+    /// NOTE: Returns the user-agent as written in the input.
+    fn find_agent(
+        user_agents: impl Iterator<Item = &'a str>,
+        user_agent: &str,
+        dialect: Dialect,
+    ) -> &'a str {
+        let crawler = dialect.agent_token(user_agent);
+        let uas = user_agents.map(|u| dialect.agent_token(u));
+        let uas = uas.filter(|u| dialect.matches_token(crawler, u));
+
+        // Length of the lowercase user-agent, see [Dialect::agent_key].
+        let len = |u: &&str| {
+            u.chars()
+                .flat_map(char::to_lowercase)
+                .map(char::len_utf8)
+                .sum::<usize>()
+        };
+        let uas = uas.max_by_key(len);
+        uas.unwrap_or(DEFAULT)
+    }
+
+    /// Creates a new `RobotsRef` from the byte slice.
+    pub fn from_slice(robots: &'a [u8], user_agent: &str) -> Self {
+        Self::from_slice_with(robots, user_agent, &ParserOptions::default())
+    }
+
+    /// Creates a new `RobotsRef` from the byte slice with the specified options.
+    pub fn from_slice_with(robots: &'a [u8], user_agent: &str, options: &ParserOptions) -> Self {
+        let dialect = options.dialect;
+        let (robots, truncated) = options.prepare(robots);
+        let tokens = into_directives_with(robots, dialect);
+
+        let uas = tokens.iter().filter_map(|u| match u.directive() {
+            Directive::UserAgent(u) => from_utf8(u).ok(),
+            _ => None,
+        });
+
+        let user_agent = Self::find_agent(uas, user_agent, dialect);
+        let default = user_agent.eq(DEFAULT);
+
+        let mut rules = Vec::new();
+        let mut delay = None;
+        let mut sitemaps = Vec::new();
+
+        // Rules preceding any user-agent are assigned to the default one.
+        let mut applies = default;
+        let mut captures_group = false;
+        for token in &tokens {
+            match *token.directive() {
+                Directive::UserAgent(u) => {
+                    applies &= captures_group;
+                    captures_group = true;
+
+                    let u = from_utf8(u).map(|u| dialect.agent_token(u));
+                    applies |= u.is_ok_and(|u| same_agent(u, user_agent));
+                }
+
+                Directive::Sitemap(u) => sitemaps.extend(from_utf8(u).ok()),
+
+                Directive::Allow(u) | Directive::Disallow(u) => {
+                    captures_group = false;
+                    let allow = matches!(token.directive(), Directive::Allow(_));
+                    match from_utf8(u) {
+                        Ok(u) if applies => rules.push(RuleRef::new(u, allow)),
+                        _ => {}
+                    }
+                }
+
+                // Unsupported directives are ignored.
+                Directive::CrawlDelay(u) if dialect.supports_crawl_delay() => {
+                    captures_group = false;
+                    match parse_crawl_delay(u) {
+                        Some(u) if applies => delay = delay.map(|c| min(c, u)).or(Some(u)),
+                        _ => {}
+                    }
+                }
+
+                Directive::RequestRate(_) | Directive::VisitTime(_) => captures_group = false,
+                _ => {}
+            }
+        }
+
+        // Rules are sorted by length and permission.
+        rules.sort();
+        Self {
+            user_agent,
+            rules,
+            delay,
+            sitemaps,
+            truncated,
+        }
+    }
+}
+
+impl<'a> RobotsRef<'a> {
+    /// Returns the longest matching user-agent as written in the input.
+    /// NOTE: The product token for the dialects matching by it.
+    pub fn user_agent(&self) -> &'a str {
+        self.user_agent
+    }
+
+    /// Returns all rules of the user-agent sorted by precedence.
+    pub fn rules(&self) -> &[RuleRef<'a>] {
+        &self.rules
+    }
+
+    /// Returns true if the path is allowed for the user-agent.
+    /// NOTE: Expects relative path.
+    pub fn is_allowed(&self, path: &str) -> bool {
+        let path = normalize_path(path);
+        if path.eq(ROBOTS_TXT) {
+            return true;
+        }
+
+        let rule = self.rules.iter().find(|u| u.is_match(path.as_str()));
+        rule.is_none_or(|u| u.is_allowed())
+    }
+
+    /// Returns the crawl-delay of the user-agent.
+    pub fn crawl_delay(&self) -> Option<Duration> {
+        self.delay
+    }
+
+    /// Returns all sitemaps as written in the input.
+    /// NOTE: Sitemaps are not validated.
+    pub fn sitemaps(&self) -> &[&'a str] {
+        &self.sitemaps
+    }
+
+    /// Returns the byte the oversized input was truncated at (if any).
+    pub fn truncated(&self) -> Option<usize> {
+        self.truncated
+    }
+}
+
+#[cfg(test)]
+mod borrowing {
+    use super::*;
+    use crate::parse::Robots;
+
+    static TXT: &[u8] = b"Disallow: /0\n\
+        User-Agent: FooBot\n\
+        User-Agent: BarBot/2.0\n\
+        Disallow: /1\x00\
+        Crawl-Delay: 1.5\n\
+        User-Agent: foobot\n\
+        Allow: /1/a\n\
+        Disallow: /*.php$\n\
+        Disallow: /%7e/\xe3\x83\x84\n\
+        Sitemap: https://example.com/sitemap.xml\n\
+        User-Agent: *\n\
+        Disallow: /3\n\
+        User-Agent: yandex\n\
+        Crawl-Delay: 2\n\
+        Disallow:\n";
+
+    #[test]
+    fn equivalence() {
+        let paths = [
+            "/0",
+            "/1",
+            "/1/a",
+            "/2.php",
+            "/2.php5",
+            "/3",
+            "/~/ツ",
+            "/robots.txt",
+        ];
+
+        let dialects = [Dialect::Lenient, Dialect::Standard, Dialect::Yandex];
+        let uas = [
+            "foobot",
+            "BarBot/2.0",
+            "barbot",
+            "foobot-image",
+            "YandexBot",
+            "nobot",
+        ];
+        for dialect in dialects {
+            let options = ParserOptions::default().dialect(dialect);
+            for ua in uas {
+                let r = Robots::from_slice_with(TXT, ua, &options);
+                let b = RobotsRef::from_slice_with(TXT, ua, &options);
+                for path in paths {
+                    let (expected, found) = (r.is_allowed(path), b.is_allowed(path));
+                    assert_eq!(expected, found, "{dialect:?} {ua} {path}");
+                }
+
+                assert_eq!(r.crawl_delay(), b.crawl_delay(), "{dialect:?} {ua}");
+                assert_eq!(r.rules().len(), b.rules().len(), "{dialect:?} {ua}");
+            }
+        }
+    }
+
+    #[test]
+    fn borrowed() {
+        let r = RobotsRef::from_slice(TXT, "foobot");
+        assert_eq!(r.user_agent(), "foobot");
+        assert_eq!(r.sitemaps(), &["https://example.com/sitemap.xml"]);
+
+        let patterns: Vec<_> = r.rules().iter().map(|u| u.pattern()).collect();
+        assert_eq!(patterns, vec!["/~/%E3%83%84", "/*.php$", "/1/a", "/1"]);
+
+        let borrowed: Vec<_> = r.rules().iter().map(|u| u.is_borrowed()).collect();
+        assert_eq!(borrowed, vec![false, true, true, true]);
+    }
+
+    #[test]
+    fn truncated() {
+        let options = ParserOptions::default().limit(0);
+        let txt = b"User-Agent: *\nDisallow: /a\n".repeat(crate::parse::BYTES_LIMIT);
+        let r = RobotsRef::from_slice_with(&txt, "foobot", &options);
+        assert_eq!(r.truncated(), Some(crate::parse::BYTES_LIMIT));
+        assert!(!r.is_allowed("/a"));
+    }
+}
@@ -0,0 +1,157 @@
+use url::Url;
+
+use crate::parse::{normalize_path, Position, Rule};
+
+/// Yandex limits the `clean-param` value to 500 characters.
+/// See [Using Clean-param](https://yandex.com/support/webmaster/robot-workings/clean-param.html).
+const CLEAN_PARAM_LIMIT: usize = 500;
+
+/// The `CleanParam` struct represents the Yandex `clean-param` directive,
+/// i.e. the query parameters that do not affect the page content
+/// of the paths matching the prefix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CleanParam {
+    params: Vec<String>,
+    rule: Rule,
+}
+
+impl CleanParam {
+    /// Creates a new `CleanParam` with the specified parameters and path prefix.
+    pub fn new(params: &[&str], path: &str) -> Self {
+        let params = params.iter().map(|u| u.to_string()).collect();
+        let rule = Rule::new(path, true);
+        Self { params, rule }
+    }
+
+    /// Attaches the position of the directive in the original input.
+    pub fn with_position(mut self, position: Position) -> Self {
+        self.rule = self.rule.with_position(position);
+        self
+    }
+
+    /// Returns the parameters to strip.
+    pub fn params(&self) -> &[String] {
+        &self.params
+    }
+
+    /// Returns the normalized path prefix.
+    pub fn path(&self) -> &str {
+        self.rule.pattern()
+    }
+
+    /// Returns the position of the directive in the original input.
+    /// NOTE: Only available for the parsed directives.
+    pub fn position(&self) -> Option<Position> {
+        self.rule.position()
+    }
+
+    /// Returns true if the normalized relative path matches the prefix.
+    /// NOTE: Expects normalized relative path without the query.
+    pub fn is_match(&self, path: &str) -> bool {
+        self.rule.is_match(path)
+    }
+}
+
+/// Parses the `clean-param` value, i.e. `p0[&p1&p2..] [path]`,
+/// into the `CleanParam`.
+pub(crate) fn parse_clean_param(u: &[u8]) -> Option<CleanParam> {
+    let u = std::str::from_utf8(u).ok()?;
+    if u.chars().count() > CLEAN_PARAM_LIMIT {
+        return None;
+    }
+
+    let mut u = u.split_whitespace();
+    let (params, path) = (u.next()?, u.next().unwrap_or("/"));
+    if u.next().is_some() {
+        return None;
+    }
+
+    let params: Vec<_> = params.split('&').collect();
+    if params.iter().any(|u| u.is_empty()) {
+        return None;
+    }
+
+    Some(CleanParam::new(&params, path))
+}
+
+/// Returns the URL without the query parameters listed
+/// by all `clean-param` directives matching its path.
+pub(crate) fn strip_params(url: &Url, clean_params: &[CleanParam]) -> Url {
+    let path = normalize_path(url.path());
+    let params = clean_params.iter().filter(|u| u.is_match(&path));
+    let params: Vec<_> = params.flat_map(|u| u.params.iter()).collect();
+
+    let mut url = url.clone();
+    let query = match url.query() {
+        Some(query) if !params.is_empty() => query,
+        _ => return url,
+    };
+
+    // Compares the raw parameter names to keep the rest of the query intact.
+    let pairs = query.split('&').filter(|u| {
+        let name = u.split('=').next().unwrap_or_default();
+        !params.iter().any(|u| u.as_str().eq(name))
+    });
+
+    let query = pairs.collect::<Vec<_>>().join("&");
+    match query.is_empty() {
+        true => url.set_query(None),
+        false => url.set_query(Some(&query)),
+    }
+
+    url
+}
+
+#[cfg(test)]
+mod stripping {
+    use super::*;
+
+    fn clean(url: &str, values: &[&[u8]]) -> String {
+        let url = Url::parse(url).unwrap();
+        let values = values.iter().map(|u| parse_clean_param(u).unwrap());
+        let values: Vec<_> = values.collect();
+        strip_params(&url, &values).to_string()
+    }
+
+    #[test]
+    fn parsing() {
+        let c = parse_clean_param(b"ref&sid /forum/*.php").unwrap();
+        assert_eq!(c.params(), &["ref", "sid"]);
+        assert_eq!(c.path(), "/forum/*.php");
+
+        let c = parse_clean_param(b"ref").unwrap();
+        assert_eq!(c.path(), "/");
+
+        assert!(parse_clean_param(b"").is_none());
+        assert!(parse_clean_param(b"a&&b /").is_none());
+        assert!(parse_clean_param(b"a / /b").is_none());
+        assert!(parse_clean_param(&b"a".repeat(501)).is_none());
+    }
+
+    #[test]
+    fn params() {
+        let r = clean("https://example.com/a?ref=1&id=2&sid=3", &[b"ref&sid"]);
+        assert_eq!(r, "https://example.com/a?id=2");
+
+        let r = clean("https://example.com/a?ref=1&sid#b", &[b"ref&sid"]);
+        assert_eq!(r, "https://example.com/a#b");
+
+        let r = clean("https://example.com/a?Ref=1&reference=2", &[b"ref"]);
+        assert_eq!(r, "https://example.com/a?Ref=1&reference=2");
+    }
+
+    #[test]
+    fn paths() {
+        let values: &[&[u8]] = &[b"ref /forum/", b"sid /*.php"];
+
+        let r = clean("https://example.com/forum/a.php?ref=1&sid=2", values);
+        assert_eq!(r, "https://example.com/forum/a.php");
+
+        let r = clean("https://example.com/blog/?ref=1&sid=2", values);
+        assert_eq!(r, "https://example.com/blog/?ref=1&sid=2");
+
+        let r = clean("https://example.com/blog/a.php?ref=1&sid=2", values);
+        assert_eq!(r, "https://example.com/blog/a.php?ref=1");
+    }
+}
@@ -0,0 +1,159 @@
+/// The `Keyword` enum represents the key of every supported directive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Keyword {
+    UserAgent,
+    Allow,
+    Disallow,
+    CrawlDelay,
+    Sitemap,
+    CleanParam,
+    Host,
+    RequestRate,
+    VisitTime,
+}
+
+/// The `Dialect` enum represents the interpretation of the `robots.txt`
+/// file by the specific search engine. See [crate::ParserOptions::dialect].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Dialect {
+    /// The permissive interpretation: accepts frequent misspellings,
+    /// matches user-agents by prefix and supports `crawl-delay`.
+    #[default]
+    Lenient,
+    /// The strict [Request for Comments: 9309](https://www.rfc-editor.org/rfc/rfc9309.txt):
+    /// no misspellings, exact product token matching, no `crawl-delay`.
+    Standard,
+    /// The [Google](https://github.com/google/robotstxt) open-source parser:
+    /// accepts misspelled `user-agent` and `disallow`, exact product token
+    /// matching, no `crawl-delay`.
+    Google,
+    /// The Bing interpretation: no misspellings, exact product token
+    /// matching, supports `crawl-delay`.
+    Bing,
+    /// The Yandex interpretation: no misspellings, matches user-agents
+    /// by prefix (e.g. `Yandex` matches `YandexBot`), no `crawl-delay`.
+    Yandex,
+}
+
+impl Dialect {
+    /// Returns all accepted spellings of the directive key.
+    /// NOTE: The canonical spelling always comes first,
+    /// unsupported directives have no spellings.
+    pub(crate) fn spellings(&self, keyword: Keyword) -> &'static [&'static str] {
+        use Dialect::*;
+        use Keyword::*;
+
+        match (keyword, self) {
+            (UserAgent, Lenient | Google) => &["user-agent", "user agent", "useragent"],
+            (UserAgent, _) => &["user-agent"],
+            (Allow, Lenient) => &["allow", "alow", "allaw"],
+            (Allow, _) => &["allow"],
+            (Disallow, Lenient | Google) => &[
+                "disallow",
+                "dissallow",
+                "dissalow",
+                "disalow",
+                "diasllow",
+                "disallaw",
+            ],
+            (Disallow, _) => &["disallow"],
+            (CrawlDelay, Lenient) => &["crawl-delay", "crawl delay", "crawldelay"],
+            (CrawlDelay, _) => &["crawl-delay"],
+            (Sitemap, Lenient) => &["sitemap", "site-map", "site map"],
+            (Sitemap, Google) => &["sitemap", "site-map"],
+            (Sitemap, _) => &["sitemap"],
+            (CleanParam, Lenient | Yandex) => &["clean-param"],
+            (CleanParam, _) => &[],
+            (Host, Lenient | Yandex) => &["host"],
+            (Host, _) => &[],
+            (RequestRate, Lenient) => &["request-rate", "request rate", "requestrate"],
+            (RequestRate, _) => &[],
+            (VisitTime, Lenient) => &["visit-time", "visit time", "visittime"],
+            (VisitTime, _) => &[],
+        }
+    }
+
+    /// Returns true if the `crawl-delay` directive is supported.
+    pub(crate) fn supports_crawl_delay(&self) -> bool {
+        matches!(self, Self::Lenient | Self::Bing)
+    }
+
+    /// Returns true if the user-agents are matched by prefix
+    /// rather than by the product token.
+    pub(crate) fn matches_prefix(&self) -> bool {
+        matches!(self, Self::Lenient | Self::Yandex)
+    }
+
+    /// Returns the user-agent in the form suitable for matching, i.e.
+    /// the lowercase user-agent or its lowercase product token.
+    pub(crate) fn agent_key(&self, user_agent: &str) -> String {
+        self.agent_token(user_agent).to_lowercase()
+    }
+
+    /// Returns the part of the user-agent used for matching, i.e.
+    /// the trimmed user-agent or its product token. See [Dialect::agent_key].
+    pub(crate) fn agent_token<'a>(&self, user_agent: &'a str) -> &'a str {
+        let user_agent = user_agent.trim();
+        if self.matches_prefix() {
+            return user_agent;
+        }
+
+        if user_agent.starts_with('*') {
+            return &user_agent[..1];
+        }
+
+        // The product token consists of letters, underscores and hyphens.
+        let token = user_agent.find(|c: char| !(c.is_ascii_alphabetic() || c == '_' || c == '-'));
+        &user_agent[..token.unwrap_or(user_agent.len())]
+    }
+
+    /// Returns true if the user-agent from the file applies to the crawler.
+    /// NOTE: Expects both user-agents to be passed through `agent_key`.
+    pub(crate) fn matches_agent(&self, crawler: &str, user_agent: &str) -> bool {
+        match self.matches_prefix() {
+            true => crawler.starts_with(user_agent),
+            false => crawler.eq(user_agent),
+        }
+    }
+
+    /// Returns true if the user-agent from the file applies to the crawler
+    /// comparing them case-insensitively without allocating.
+    /// NOTE: Expects both user-agents to be passed through `agent_token`.
+    pub(crate) fn matches_token(&self, crawler: &str, user_agent: &str) -> bool {
+        let mut crawler = crawler.chars().flat_map(char::to_lowercase);
+        let mut user_agent = user_agent.chars().flat_map(char::to_lowercase);
+        match self.matches_prefix() {
+            true => user_agent.all(|u| crawler.next() == Some(u)),
+            false => crawler.eq(user_agent),
+        }
+    }
+}
+
+#[cfg(test)]
+mod agents {
+    use super::*;
+
+    #[test]
+    fn keys() {
+        let d = Dialect::Standard;
+        assert_eq!(d.agent_key(" FooBot/1.2 (+https://example.com)"), "foobot");
+        assert_eq!(d.agent_key("* # all"), "*");
+        assert_eq!(d.agent_key("foo_bar-bot"), "foo_bar-bot");
+
+        let d = Dialect::Lenient;
+        assert_eq!(d.agent_key(" FooBot/1.2 "), "foobot/1.2");
+    }
+
+    #[test]
+    fn matching() {
+        assert!(Dialect::Yandex.matches_agent("yandexbot", "yandex"));
+        assert!(!Dialect::Google.matches_agent("googlebot-image", "googlebot"));
+        assert!(Dialect::Google.matches_agent("googlebot", "googlebot"));
+
+        assert!(Dialect::Yandex.matches_token("YandexBot", "yandex"));
+        assert!(!Dialect::Yandex.matches_token("Yan", "yandex"));
+        assert!(!Dialect::Google.matches_token("Googlebot-Image", "googlebot"));
+        assert!(Dialect::Google.matches_token("GoogleBot", "googlebot"));
+    }
+}
@@ -0,0 +1,527 @@
+use std::cmp::min;
+use std::collections::HashMap;
+use std::io::{Error as IoError, ErrorKind, Read};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use url::Url;
+
+use crate::parse::{decode, parse_clean_param, parse_host, CleanParam, Host, Token};
+use crate::parse::{into_directives_with, Dialect, Directive, ParserOptions, Robots, Rule, Rules};
+use crate::parse::{parse_request_rate, parse_visit_time, RequestRate, StreamParser, VisitTime};
+
+/// The size of the chunk the reader is read by.
+pub(crate) const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Parses the `user-agent` value into the lowercase string.
+pub(crate) fn parse_user_agent(u: &[u8]) -> Option<String> {
+    let u = std::str::from_utf8(u).ok()?;
+    let u = u.trim().to_lowercase();
+    Some(u)
+}
+
+/// Parses the `sitemap` value into the absolute URL.
+pub(crate) fn parse_sitemap(u: &[u8]) -> Option<Url> {
+    let u = std::str::from_utf8(u).ok()?;
+    let u = Url::parse(u).ok()?;
+    Some(u)
+}
+
+/// Parses the `allow` or `disallow` value into the `Rule`.
+pub(crate) fn parse_rule(u: &[u8], allow: bool) -> Option<Rule> {
+    let u = std::str::from_utf8(u).ok()?;
+    Some(Rule::new(u, allow))
+}
+
+/// Parses the `crawl-delay` value into the `Duration`.
+pub(crate) fn parse_crawl_delay(u: &[u8]) -> Option<Duration> {
+    let u = std::str::from_utf8(u).ok()?;
+    let u = u.parse::<f64>().ok()?;
+    let u = Duration::try_from_secs_f64(u).ok()?;
+    Some(u)
+}
+
+/// The `Group` struct represents the set of rules shared by
+/// the consecutive `user-agent` directives.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Group {
+    user_agents: Vec<String>,
+    rules: Vec<Rule>,
+    delay: Option<Duration>,
+    rate: Option<RequestRate>,
+    visit_times: Vec<VisitTime>,
+}
+
+impl Group {
+    /// Returns all lowercase user-agents of the group.
+    /// NOTE: Empty for the rules preceding any `user-agent`.
+    pub fn user_agents(&self) -> &[String] {
+        &self.user_agents
+    }
+
+    /// Returns all rules of the group in the original order.
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Returns the shortest crawl-delay of the group.
+    pub fn crawl_delay(&self) -> Option<Duration> {
+        self.delay
+    }
+
+    /// Returns the fastest request-rate of the group.
+    pub fn request_rate(&self) -> Option<RequestRate> {
+        self.rate
+    }
+
+    /// Returns all visit-time windows of the group.
+    pub fn visit_times(&self) -> &[VisitTime] {
+        &self.visit_times
+    }
+}
+
+/// The `Document` struct represents all groups of the `robots.txt` file.
+/// It is parsed once and provides `Robots` for any number of user-agents.
+///
+/// ```rust
+/// use robotxt::Document;
+///
+/// let txt = r#"
+///     User-Agent: foobot
+///     Disallow: /example/
+///     User-Agent: barbot
+///     Disallow: /
+/// "#.as_bytes();
+///
+/// let doc = Document::from_slice(txt);
+/// assert!(!doc.robots("foobot").is_allowed("/example/nope.txt"));
+/// assert!(doc.robots("foobot").is_allowed("/yeah.txt"));
+/// assert!(!doc.robots("barbot").is_allowed("/yeah.txt"));
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Document {
+    groups: Vec<Group>,
+    sitemaps: Vec<Url>,
+    clean_params: Vec<CleanParam>,
+    host: Option<Host>,
+    truncated: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    replaced: Option<usize>,
+    dialect: Dialect,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    cache: RulesCache,
+}
+
+/// The `RulesCache` struct holds the merged `Rules` of every
+/// matched user-agent, so the views are built only once.
+#[derive(Debug, Default)]
+struct RulesCache(Mutex<HashMap<String, Arc<Rules>>>);
+
+impl Clone for RulesCache {
+    fn clone(&self) -> Self {
+        let cache = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        Self(Mutex::new(cache.clone()))
+    }
+}
+
+/// The `Collector` struct accumulates the directives into the `Document`
+/// one at a time. See [crate::StreamParser].
+#[derive(Debug, Default)]
+pub(crate) struct Collector {
+    groups: Vec<Group>,
+    group: Option<Group>,
+    captures_group: bool,
+    sitemaps: Vec<Url>,
+    clean_params: Vec<CleanParam>,
+    host: Option<Host>,
+    dialect: Dialect,
+}
+
+impl Collector {
+    /// Creates a new empty `Collector` of the specified dialect.
+    pub fn new(dialect: Dialect) -> Self {
+        Self {
+            dialect,
+            ..Self::default()
+        }
+    }
+
+    /// Adds the directive to the current group or the document.
+    pub fn push(&mut self, token: &Token) {
+        let directive = token.directive();
+        match directive {
+            Directive::UserAgent(u) => {
+                if !self.captures_group {
+                    self.groups.extend(self.group.take());
+                }
+
+                let group = self.group.get_or_insert_with(Group::default);
+                if let Some(u) = parse_user_agent(u) {
+                    group.user_agents.push(u);
+                }
+
+                self.captures_group = true;
+                return;
+            }
+
+            Directive::Sitemap(u) => {
+                if let Some(u) = parse_sitemap(u) {
+                    self.sitemaps.push(u);
+                }
+
+                return;
+            }
+
+            // Clean-param is not bound to the group.
+            Directive::CleanParam(u) => {
+                if let Some(u) = parse_clean_param(u) {
+                    self.clean_params.push(u.with_position(token.position()));
+                }
+
+                return;
+            }
+
+            // The first valid host wins.
+            Directive::Host(u) => {
+                if self.host.is_none() {
+                    self.host = parse_host(u);
+                }
+
+                return;
+            }
+
+            Directive::Unknown(_) => return,
+            // Unsupported directives are ignored.
+            Directive::CrawlDelay(_) if !self.dialect.supports_crawl_delay() => return,
+            _ => self.captures_group = false,
+        }
+
+        let group = self.group.get_or_insert_with(Group::default);
+        match directive {
+            Directive::Allow(u) | Directive::Disallow(u) => {
+                let allow = matches!(directive, Directive::Allow(_));
+                if let Some(u) = parse_rule(u, allow) {
+                    group.rules.push(u.with_position(token.position()))
+                }
+            }
+
+            Directive::CrawlDelay(u) => {
+                if let Some(u) = parse_crawl_delay(u) {
+                    group.delay = group.delay.map(|c| min(c, u)).or(Some(u));
+                }
+            }
+
+            Directive::RequestRate(u) => {
+                if let Some(u) = parse_request_rate(u) {
+                    let rate = group.rate.filter(|c| c.interval() <= u.interval());
+                    group.rate = rate.or(Some(u));
+                }
+            }
+
+            Directive::VisitTime(u) => {
+                if let Some(u) = parse_visit_time(u) {
+                    group.visit_times.push(u);
+                }
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns the `Document` of all added directives.
+    pub fn finish(mut self, truncated: Option<usize>, replaced: Option<usize>) -> Document {
+        self.groups.extend(self.group);
+        Document {
+            groups: self.groups,
+            sitemaps: self.sitemaps,
+            clean_params: self.clean_params,
+            host: self.host,
+            truncated,
+            replaced,
+            dialect: self.dialect,
+            cache: RulesCache::default(),
+        }
+    }
+}
+
+impl Document {
+    /// Creates a new `Document` from the directives.
+    pub(crate) fn from_directives(directives: &[Token], dialect: Dialect) -> Self {
+        let mut collector = Collector::new(dialect);
+        for token in directives {
+            collector.push(token);
+        }
+
+        collector.finish(None, None)
+    }
+
+    /// Creates a new `Document` from the byte slice.
+    pub fn from_slice(robots: &[u8]) -> Self {
+        Self::from_slice_with(robots, &ParserOptions::default())
+    }
+
+    /// Creates a new `Document` from the byte slice with the specified options.
+    pub fn from_slice_with(robots: &[u8], options: &ParserOptions) -> Self {
+        let (robots, replaced) = decode(robots, options.charset, options.limit);
+        let (robots, truncated) = options.prepare(&robots);
+        let directives = into_directives_with(robots, options.dialect);
+
+        let mut document = Self::from_directives(directives.as_slice(), options.dialect);
+        document.truncated = truncated;
+        document.replaced = replaced.filter(|u| *u < robots.len());
+        document
+    }
+
+    /// Creates a new `Document` from the generic reader.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, IoError> {
+        Self::from_reader_with(reader, &ParserOptions::default())
+    }
+
+    /// Creates a new `Document` from the generic reader with the specified options.
+    /// NOTE: Lexes the input as it is read. See [crate::StreamParser].
+    pub fn from_reader_with<R: Read>(reader: R, options: &ParserOptions) -> Result<Self, IoError> {
+        let mut reader = reader;
+        let mut parser = StreamParser::new(options);
+
+        let mut chunk = [0; CHUNK_SIZE];
+        loop {
+            let read = match reader.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+
+            // Stops reading the oversized input.
+            if parser.push(&chunk[..read]).is_break() {
+                break;
+            }
+        }
+
+        Ok(parser.finish())
+    }
+}
+
+impl Document {
+    /// Returns all groups in the original order.
+    pub fn groups(&self) -> &[Group] {
+        &self.groups
+    }
+
+    /// Returns all user-agents in the original order.
+    pub fn user_agents(&self) -> impl Iterator<Item = &str> {
+        let uas = self.groups.iter().flat_map(|g| g.user_agents.iter());
+        uas.map(|u| u.as_str())
+    }
+
+    /// Returns all sitemaps.
+    pub fn sitemaps(&self) -> &Vec<Url> {
+        &self.sitemaps
+    }
+
+    /// Returns all `clean-param` directives in the original order.
+    pub fn clean_params(&self) -> &[CleanParam] {
+        &self.clean_params
+    }
+
+    /// Returns the preferred mirror (if any).
+    pub fn host(&self) -> Option<&Host> {
+        self.host.as_ref()
+    }
+
+    /// Returns the byte the oversized input was truncated at (if any).
+    pub fn truncated(&self) -> Option<usize> {
+        self.truncated
+    }
+
+    /// Returns the byte the first malformed sequence
+    /// was replaced at while transcoding (if any).
+    pub fn replaced(&self) -> Option<usize> {
+        self.replaced
+    }
+
+    /// Returns the interpretation the file was parsed with.
+    pub fn dialect(&self) -> Dialect {
+        self.dialect
+    }
+
+    /// Returns the merged rules of all groups assigned to
+    /// the longest matching user-agent and the user-agent itself.
+    /// NOTE: The rules are merged once per matched user-agent.
+    pub(crate) fn merge(&self, user_agent: &str) -> (String, Arc<Rules>) {
+        let dialect = self.dialect;
+        let (user_agent, default) = Robots::find_agent(self.user_agents(), user_agent, dialect);
+
+        let mut cache = self.cache.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(rules) = cache.get(&user_agent) {
+            return (user_agent, rules.clone());
+        }
+
+        let rules = Arc::new(self.merge_groups(&user_agent, default));
+        cache.insert(user_agent.clone(), rules.clone());
+        (user_agent, rules)
+    }
+
+    /// Merges the rules of all groups assigned to the user-agent.
+    fn merge_groups(&self, user_agent: &str, default: bool) -> Rules {
+        let dialect = self.dialect;
+
+        // Rules preceding any user-agent are assigned to the default one.
+        let groups = self
+            .groups
+            .iter()
+            .filter(|g| match g.user_agents.is_empty() {
+                true => default,
+                false => g
+                    .user_agents
+                    .iter()
+                    .any(|u| dialect.agent_key(u) == user_agent),
+            });
+
+        let mut rules = Vec::new();
+        let mut delay = None;
+        let mut rate: Option<RequestRate> = None;
+        let mut visit_times = Vec::new();
+        for group in groups {
+            rules.extend(group.rules.iter().cloned());
+            if let Some(u) = group.delay {
+                delay = delay.map(|c| min(c, u)).or(Some(u));
+            }
+
+            if let Some(u) = group.rate {
+                rate = rate.filter(|c| c.interval() <= u.interval()).or(Some(u));
+            }
+
+            visit_times.extend(group.visit_times.iter().copied());
+        }
+
+        let rules = Rules::new(rules, delay).with_request_rate(rate);
+        rules.with_visit_times(visit_times)
+    }
+
+    /// Returns the `Rules` for the specified user-agent.
+    /// NOTE: Returns the copy of the cached rules, see [Document::robots].
+    pub fn rules(&self, user_agent: &str) -> Rules {
+        self.merge(user_agent).1.as_ref().clone()
+    }
+
+    /// Returns the `Robots` for the specified user-agent.
+    /// NOTE: The rules are merged and indexed on the first call for the
+    /// matched user-agent and shared by all later views.
+    pub fn robots(&self, user_agent: &str) -> Robots {
+        Robots::from_document(self, user_agent)
+    }
+}
+
+#[cfg(test)]
+mod grouping {
+    use super::*;
+
+    static TXT: &[u8] = b"Disallow: /0\n\
+        User-Agent: foobot\n\
+        User-Agent: barbot\n\
+        # comment\n\
+        Disallow: /1\n\
+        Crawl-Delay: 5\n\
+        User-Agent: foobot\n\
+        Disallow: /2\n\
+        Crawl-Delay: 2\n\
+        Sitemap: https://example.com/sitemap.xml\n\
+        User-Agent: *\n\
+        Disallow: /3\n";
+
+    #[test]
+    fn groups() {
+        let r = Document::from_slice(TXT);
+        let uas: Vec<_> = r.groups().iter().map(|g| g.user_agents()).collect();
+        assert_eq!(
+            uas,
+            vec![&[][..], &["foobot", "barbot"], &["foobot"], &["*"]]
+        );
+
+        let rules: Vec<_> = r.groups().iter().map(|g| g.rules().len()).collect();
+        assert_eq!(rules, vec![1, 1, 1, 1]);
+        assert_eq!(r.sitemaps().len(), 1);
+    }
+
+    #[test]
+    fn merged() {
+        let r = Document::from_slice(TXT);
+
+        let foobot = r.robots("foobot");
+        assert_eq!(foobot.user_agent(), "foobot");
+        assert_eq!(foobot.crawl_delay(), Some(Duration::from_secs(2)));
+        assert!(!foobot.is_allowed("/1") && !foobot.is_allowed("/2"));
+        assert!(foobot.is_allowed("/0") && foobot.is_allowed("/3"));
+
+        let barbot = r.robots("barbot");
+        assert!(!barbot.is_allowed("/1") && barbot.is_allowed("/2"));
+
+        assert_eq!(r.truncated(), None);
+        let nobot = r.rules("nobot");
+        assert!(!nobot.is_allowed("/0") && !nobot.is_allowed("/3"));
+        assert!(nobot.is_allowed("/1") && nobot.is_allowed("/2"));
+    }
+
+    #[test]
+    fn schedule() {
+        let txt = b"User-Agent: foobot\nRequest-Rate: 1/10s\nVisit-Time: 0100-0200\n\
+            User-Agent: barbot\nUser-Agent: foobot\nRequest-Rate: 1/5s\nVisit-Time: 0300-0400";
+        let r = Document::from_slice(txt);
+        assert_eq!(r.groups().len(), 2);
+
+        let foobot = r.robots("foobot");
+        let rate = foobot.request_rate().unwrap();
+        assert_eq!(rate.interval(), Duration::from_secs(5));
+        assert_eq!(foobot.visit_times().len(), 2);
+
+        let options = ParserOptions::default().dialect(Dialect::Standard);
+        let r = Document::from_slice_with(txt, &options);
+        assert!(r.robots("foobot").request_rate().is_none());
+    }
+
+    #[test]
+    fn replaced() {
+        use crate::parse::{Charset, BYTES_LIMIT};
+
+        let utf16 = |u: &str| -> Vec<u8> { u.encode_utf16().flat_map(u16::to_le_bytes).collect() };
+        let options = ParserOptions::default().charset(Charset::for_label("utf-16le").unwrap());
+        let mut txt = utf16("User-Agent: *\nDisallow: /a");
+        txt.extend([0x00, 0xdc]);
+        txt.extend(utf16("\nDisallow: /b\n"));
+
+        let r = Document::from_slice_with(&txt, &options);
+        assert_eq!(r.replaced(), Some(26));
+        assert_eq!(r.robots("foobot").replaced(), Some(26));
+        let r = Document::from_reader_with(txt.as_slice(), &options).unwrap();
+        assert_eq!(r.replaced(), Some(26));
+        assert!(!r.robots("foobot").is_allowed("/b"));
+
+        // The replaced sequence after the limit is ignored.
+        let mut txt = utf16(&"#".repeat(BYTES_LIMIT));
+        txt.extend([0x00, 0xdc]);
+        let r = Document::from_slice_with(&txt, &options);
+        assert_eq!((r.truncated(), r.replaced()), (Some(BYTES_LIMIT), None));
+        let r = Document::from_reader_with(txt.as_slice(), &options).unwrap();
+        assert_eq!((r.truncated(), r.replaced()), (Some(BYTES_LIMIT), None));
+        assert_eq!(Document::from_slice(TXT).replaced(), None);
+    }
+
+    #[test]
+    fn cached() {
+        let r = Document::from_slice(TXT);
+        let (ua, foobot) = r.merge("FooBot/1.0");
+        let (_, again) = r.merge("foobot");
+        assert_eq!(ua, "foobot");
+        assert!(Arc::ptr_eq(&foobot, &again));
+
+        let (_, nobot) = r.merge("nobot");
+        assert!(!Arc::ptr_eq(&foobot, &nobot));
+
+        // Copies share the already merged rules.
+        let (_, copied) = r.clone().merge("foobot");
+        assert!(Arc::ptr_eq(&foobot, &copied));
+    }
+}
@@ -0,0 +1,489 @@
+use std::borrow::Cow;
+use std::mem::take;
+
+use encoding_rs::{Decoder, DecoderResult, Encoding};
+use encoding_rs::{ISO_2022_JP, REPLACEMENT, UTF_16BE, UTF_16LE, UTF_8};
+
+use crate::parse::CHUNK_SIZE;
+
+/// The `Codec` enum represents the decoder of the `Charset`.
+/// NOTE: UTF-32 is not supported by [encoding_rs].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Codec {
+    Utf32Le,
+    Utf32Be,
+    Encoding(&'static Encoding),
+}
+
+/// The `Charset` struct represents the character encoding of the `robots.txt` file.
+/// See [crate::ParserOptions::charset].
+///
+/// ```rust
+/// use robotxt::Charset;
+///
+/// let c = Charset::from_content_type("text/plain; charset=\"cp1251\"").unwrap();
+/// assert_eq!(c.name(), "windows-1251");
+/// assert_eq!(Charset::for_label("utf-32be").unwrap().name(), "UTF-32BE");
+///
+/// let (c, len) = Charset::sniff(b"\xff\xfeU\x00").unwrap();
+/// assert_eq!((c.name(), len), ("UTF-16LE", 2));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Charset {
+    codec: Codec,
+}
+
+impl Charset {
+    /// The default character encoding, i.e. UTF-8.
+    pub const UTF_8: Self = Self {
+        codec: Codec::Encoding(UTF_8),
+    };
+
+    /// Creates a new `Charset` from the label, e.g. `latin1` or `utf-16le`.
+    /// See [Encoding Standard](https://encoding.spec.whatwg.org/#names-and-labels).
+    /// NOTE: Labels of the ISO-8859-1 are mapped to `windows-1252`.
+    pub fn for_label(label: &str) -> Option<Self> {
+        let label = label.trim();
+        let codec = match label.to_ascii_lowercase().as_str() {
+            "utf-32le" | "utf-32" => Codec::Utf32Le,
+            "utf-32be" => Codec::Utf32Be,
+            _ => Codec::Encoding(Encoding::for_label(label.as_bytes())?),
+        };
+
+        Some(Self { codec })
+    }
+
+    /// Creates a new `Charset` from the `charset` parameter of
+    /// the `Content-Type` header value, e.g. `text/plain; charset=utf-8`.
+    pub fn from_content_type(value: &str) -> Option<Self> {
+        let params = value.split(';').skip(1);
+        let mut params = params.filter_map(|u| u.split_once('='));
+        let (_, label) = params.find(|(k, _)| k.trim().eq_ignore_ascii_case("charset"))?;
+
+        Self::for_label(label.trim().trim_matches('"'))
+    }
+
+    /// Detects the `Charset` by the byte order mark (BOM) at the start of input.
+    /// Returns the charset and the length of the mark.
+    /// NOTE: The UTF-32LE mark starts with the UTF-16LE one.
+    pub fn sniff(robots: &[u8]) -> Option<(Self, usize)> {
+        let (codec, len) = match robots {
+            [0xff, 0xfe, 0x00, 0x00, ..] => (Codec::Utf32Le, 4),
+            [0x00, 0x00, 0xfe, 0xff, ..] => (Codec::Utf32Be, 4),
+            [0xef, 0xbb, 0xbf, ..] => (Codec::Encoding(UTF_8), 3),
+            [0xff, 0xfe, ..] => (Codec::Encoding(UTF_16LE), 2),
+            [0xfe, 0xff, ..] => (Codec::Encoding(UTF_16BE), 2),
+            _ => return None,
+        };
+
+        Some((Self { codec }, len))
+    }
+
+    /// Returns the canonical name of the charset.
+    pub fn name(&self) -> &'static str {
+        match self.codec {
+            Codec::Utf32Le => "UTF-32LE",
+            Codec::Utf32Be => "UTF-32BE",
+            Codec::Encoding(u) => u.name(),
+        }
+    }
+
+    /// Returns true if the input is read as is, i.e. not transcoded.
+    pub fn is_utf8(&self) -> bool {
+        self.codec == Codec::Encoding(UTF_8)
+    }
+}
+
+/// Returns the charset the input should be transcoded from (if any)
+/// and the length of its byte order mark. The mark takes precedence
+/// over the hint, e.g. the `Content-Type` header.
+/// NOTE: The UTF-8 input is kept as is, its mark is removed by the lexer.
+pub(crate) fn detect(head: &[u8], hint: Option<Charset>) -> (Option<Charset>, usize) {
+    let charset = match Charset::sniff(head) {
+        Some((charset, _)) if charset.is_utf8() => return (None, 0),
+        Some((charset, len)) => return (Some(charset), len),
+        None => hint,
+    };
+
+    (charset.filter(|u| !u.is_utf8()), 0)
+}
+
+/// The `Transcoder` struct converts the input of the `Charset`
+/// into UTF-8 chunk by chunk, replacing malformed sequences.
+#[derive(Debug)]
+pub(crate) struct Transcoder {
+    charset: Charset,
+    decoder: Option<Decoder>,
+    // The incomplete code unit of UTF-32.
+    pending: Vec<u8>,
+    // The trailing input the decoder may still depend on.
+    tail: Vec<u8>,
+    // The number of bytes written so far.
+    written: usize,
+    replaced: Option<usize>,
+}
+
+impl Clone for Transcoder {
+    /// NOTE: The state of [encoding_rs::Decoder] cannot be copied, i.e.
+    /// the new decoder is brought to the same state by the trailing input.
+    fn clone(&self) -> Self {
+        let mut clone = Self::new(self.charset);
+        clone.pending = self.pending.clone();
+        if clone.decoder.is_some() {
+            clone.push_encoding(&self.tail, false, &mut Vec::new(), 0);
+        }
+
+        clone.written = self.written;
+        clone.replaced = self.replaced;
+        clone
+    }
+}
+
+impl Transcoder {
+    /// Creates a new `Transcoder` of the specified charset.
+    pub fn new(charset: Charset) -> Self {
+        Self {
+            charset,
+            decoder: Self::decoder(charset),
+            pending: Vec::new(),
+            tail: Vec::new(),
+            written: 0,
+            replaced: None,
+        }
+    }
+
+    /// Returns the decoder of the charset unless it is UTF-32.
+    fn decoder(charset: Charset) -> Option<Decoder> {
+        match charset.codec {
+            Codec::Encoding(u) => Some(u.new_decoder_without_bom_handling()),
+            _ => None,
+        }
+    }
+
+    /// Appends the replacement character to the output
+    /// and records the offset of the first one.
+    fn replace(&mut self, output: &mut Vec<u8>, start: usize) {
+        let offset = self.written + output.len() - start;
+        self.replaced.get_or_insert(offset);
+        output.extend_from_slice("\u{fffd}".as_bytes());
+    }
+
+    /// Transcodes the chunk and appends the result to the output.
+    /// NOTE: The incomplete sequence is kept until the next chunk
+    /// unless it is the last one.
+    pub fn push(&mut self, chunk: &[u8], last: bool, output: &mut Vec<u8>) {
+        let start = output.len();
+        match self.decoder.is_some() {
+            true => self.push_encoding(chunk, last, output, start),
+            false => self.push_utf32(chunk, last, output, start),
+        }
+
+        self.written += output.len() - start;
+    }
+
+    /// Transcodes the chunk with [encoding_rs].
+    fn push_encoding(&mut self, chunk: &[u8], last: bool, output: &mut Vec<u8>, start: usize) {
+        match last {
+            true => self.tail.clear(),
+            false => self.retain(chunk),
+        }
+
+        let mut chunk = chunk;
+        loop {
+            let decoder = self.decoder.as_mut().expect("should be some");
+            let len = decoder.max_utf8_buffer_length_without_replacement(chunk.len());
+            let len = len.unwrap_or(chunk.len() * 3).max(4);
+
+            let written = output.len();
+            output.resize(written + len, 0);
+            let decoded =
+                decoder.decode_to_utf8_without_replacement(chunk, &mut output[written..], last);
+
+            let (result, read, len) = decoded;
+            output.truncate(written + len);
+            chunk = &chunk[read..];
+            match result {
+                DecoderResult::InputEmpty => return,
+                DecoderResult::OutputFull => continue,
+                DecoderResult::Malformed(_, _) => self.replace(output, start),
+            }
+        }
+    }
+
+    /// Keeps the trailing input the decoder may still depend on, i.e.
+    /// everything after the last point its state is known to be reset at.
+    fn retain(&mut self, chunk: &[u8]) {
+        let Codec::Encoding(encoding) = self.charset.codec else {
+            return;
+        };
+
+        let tail = &mut self.tail;
+        tail.extend_from_slice(chunk);
+        // Only the first byte is ever reported as malformed.
+        if encoding == REPLACEMENT {
+            tail.truncate(1);
+            return;
+        }
+
+        let start = match encoding {
+            u if u.is_single_byte() => tail.len(),
+            // The odd byte and the preceding code unit, e.g. a high surrogate.
+            u if u == UTF_16LE || u == UTF_16BE => tail.len().saturating_sub(2 + tail.len() % 2),
+            // The last valid escape sequence that switches the mode.
+            u if u == ISO_2022_JP => tail.windows(3).rposition(is_escape).unwrap_or(0),
+            // Bytes below `b'0'` are never a part of a multibyte sequence.
+            _ => tail.iter().rposition(|u| *u < b'0').map_or(0, |u| u + 1),
+        };
+
+        tail.drain(..start);
+    }
+
+    /// Transcodes the chunk of UTF-32.
+    /// NOTE: UTF-32 is not supported by [encoding_rs].
+    fn push_utf32(&mut self, chunk: &[u8], last: bool, output: &mut Vec<u8>, start: usize) {
+        self.pending.extend_from_slice(chunk);
+        let complete = self.pending.len() - self.pending.len() % 4;
+        let pending = take(&mut self.pending);
+        for unit in pending[..complete].chunks_exact(4) {
+            let unit = [unit[0], unit[1], unit[2], unit[3]];
+            let unit = match self.charset.codec {
+                Codec::Utf32Be => u32::from_be_bytes(unit),
+                _ => u32::from_le_bytes(unit),
+            };
+
+            match char::from_u32(unit) {
+                Some(u) => output.extend_from_slice(u.encode_utf8(&mut [0; 4]).as_bytes()),
+                None => self.replace(output, start),
+            }
+        }
+
+        self.pending = pending;
+        self.pending.drain(..complete);
+        if last && !self.pending.is_empty() {
+            self.pending.clear();
+            self.replace(output, start);
+        }
+    }
+
+    /// Returns the output offset of the first replaced
+    /// malformed sequence (if any).
+    pub fn replaced(&self) -> Option<usize> {
+        self.replaced
+    }
+}
+
+/// Returns true if the bytes are the escape sequence of ISO-2022-JP.
+fn is_escape(u: &[u8]) -> bool {
+    matches!(
+        u,
+        [0x1b, b'(', b'B' | b'J' | b'I'] | [0x1b, b'$', b'@' | b'B']
+    )
+}
+
+/// Transcodes the input into UTF-8 if it is not UTF-8 according to the
+/// byte order mark or the hint. Returns the output offset of the first
+/// replaced malformed sequence (if any).
+/// NOTE: Stops once the output exceeds the limit, the rest is ignored.
+pub(crate) fn decode(
+    robots: &[u8],
+    hint: Option<Charset>,
+    limit: usize,
+) -> (Cow<'_, [u8]>, Option<usize>) {
+    let (charset, bom) = match detect(robots, hint) {
+        (Some(charset), bom) => (charset, bom),
+        (None, _) => return (Cow::Borrowed(robots), None),
+    };
+
+    let mut output = Vec::with_capacity(robots.len().min(limit));
+    let mut transcoder = Transcoder::new(charset);
+    for chunk in robots[bom..].chunks(CHUNK_SIZE) {
+        transcoder.push(chunk, false, &mut output);
+        if output.len() > limit {
+            return (Cow::Owned(output), transcoder.replaced());
+        }
+    }
+
+    transcoder.push(b"", true, &mut output);
+    (Cow::Owned(output), transcoder.replaced())
+}
+
+#[cfg(test)]
+mod decoding {
+    use super::*;
+
+    static TXT: &str = "User-Agent: фубот\r\nDisallow: /é\n";
+
+    fn utf16(txt: &str, be: bool) -> Vec<u8> {
+        let bom = [0xfeff].into_iter().chain(txt.encode_utf16());
+        let bytes = bom.map(|u| match be {
+            true => u.to_be_bytes(),
+            false => u.to_le_bytes(),
+        });
+
+        bytes.flatten().collect()
+    }
+
+    fn utf32(txt: &str, be: bool) -> Vec<u8> {
+        let bom = ['\u{feff}'].into_iter().chain(txt.chars());
+        let bytes = bom.map(|u| match be {
+            true => (u as u32).to_be_bytes(),
+            false => (u as u32).to_le_bytes(),
+        });
+
+        bytes.flatten().collect()
+    }
+
+    #[test]
+    fn labels() {
+        let c = Charset::from_content_type("text/plain;CharSet=latin1").unwrap();
+        assert_eq!(c.name(), "windows-1252");
+
+        let c = Charset::from_content_type("text/plain; format=flowed; charset=\"UTF-32\"");
+        assert_eq!(c.map(|u| u.name()), Some("UTF-32LE"));
+
+        assert_eq!(Charset::from_content_type("text/plain"), None);
+        assert_eq!(Charset::from_content_type("text/plain; charset=foo"), None);
+        assert!(Charset::for_label("utf8").unwrap().is_utf8());
+    }
+
+    #[test]
+    fn marks() {
+        let cases = [
+            (utf16(TXT, false), "UTF-16LE"),
+            (utf16(TXT, true), "UTF-16BE"),
+            (utf32(TXT, false), "UTF-32LE"),
+            (utf32(TXT, true), "UTF-32BE"),
+        ];
+
+        for (txt, name) in cases {
+            let (charset, _) = Charset::sniff(&txt).unwrap();
+            assert_eq!(charset.name(), name);
+
+            // The mark takes precedence over the hint.
+            let (decoded, replaced) = decode(&txt, Charset::for_label("windows-1251"), usize::MAX);
+            assert_eq!(decoded.as_ref(), TXT.as_bytes(), "{name}");
+            assert_eq!(replaced, None, "{name}");
+        }
+
+        let txt = [b"\xef\xbb\xbf", TXT.as_bytes()].concat();
+        let (decoded, _) = decode(&txt, Charset::for_label("windows-1251"), usize::MAX);
+        assert!(matches!(decoded, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn legacy() {
+        let txt = b"User-Agent: *\nDisallow: /\xf4\xf3\n";
+        let (decoded, replaced) = decode(txt, Charset::for_label("cp1251"), usize::MAX);
+        assert_eq!(
+            decoded.as_ref(),
+            "User-Agent: *\nDisallow: /фу\n".as_bytes()
+        );
+        assert_eq!(replaced, None);
+
+        let hint = Charset::for_label("iso-8859-1");
+        let (decoded, replaced) = decode(b"Disallow: /\xe9", hint, usize::MAX);
+        assert_eq!(decoded.as_ref(), "Disallow: /é".as_bytes());
+        assert_eq!(replaced, None);
+
+        let (decoded, _) = decode(txt, None, usize::MAX);
+        assert!(matches!(decoded, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn lossy() {
+        let mut txt = utf16(TXT, false);
+        txt.extend([0x00, 0xd8]);
+        let (decoded, replaced) = decode(&txt, None, usize::MAX);
+        assert!(decoded.ends_with("\u{fffd}".as_bytes()));
+        assert_eq!(replaced, Some(TXT.len()));
+
+        let mut txt = utf32(TXT, true);
+        txt.extend([0x00, 0x11, 0x00, 0x00, 0x00]);
+        let (decoded, replaced) = decode(&txt, None, usize::MAX);
+        assert!(decoded.ends_with("\u{fffd}\u{fffd}".as_bytes()));
+        assert_eq!(replaced, Some(TXT.len()));
+
+        // The replacement character of the input is not replaced.
+        let mut txt = utf16("Disallow: /\u{fffd}\n", false);
+        txt.extend([0x00, 0xdc]);
+        let (decoded, replaced) = decode(&txt, None, usize::MAX);
+        assert_eq!(decoded.as_ref(), "Disallow: /\u{fffd}\n\u{fffd}".as_bytes());
+        assert_eq!(replaced, Some(15));
+    }
+
+    #[test]
+    fn limit() {
+        let txt = utf16(&TXT.repeat(CHUNK_SIZE), true);
+        let (decoded, _) = decode(&txt, None, TXT.len());
+        assert!(decoded.len() > TXT.len());
+        assert!(decoded.len() <= TXT.len() + CHUNK_SIZE * 2);
+        assert!(decoded.starts_with(TXT.as_bytes()));
+
+        let txt = utf16(TXT, true);
+        let (decoded, _) = decode(&txt, None, TXT.len());
+        assert_eq!(decoded.as_ref(), TXT.as_bytes());
+    }
+
+    #[test]
+    fn chunks() {
+        let txt = utf32(TXT, false);
+        let (charset, bom) = Charset::sniff(&txt).unwrap();
+        for size in 1..=8 {
+            let mut output = Vec::new();
+            let mut transcoder = Transcoder::new(charset);
+            for chunk in txt[bom..].chunks(size) {
+                transcoder.push(chunk, false, &mut output);
+            }
+
+            transcoder.push(b"", true, &mut output);
+            assert_eq!(output, TXT.as_bytes(), "{size}");
+            assert_eq!(transcoder.replaced(), None);
+        }
+    }
+
+    /// Transcodes the input split in two with the clone taken in between.
+    fn split(charset: Charset, txt: &[u8], at: usize) -> [(Vec<u8>, Option<usize>); 2] {
+        let mut transcoder = Transcoder::new(charset);
+        let mut output = Vec::new();
+        transcoder.push(&txt[..at], false, &mut output);
+
+        let mut cloned = transcoder.clone();
+        let mut cloned_output = output.clone();
+        transcoder.push(&txt[at..], true, &mut output);
+        cloned.push(&txt[at..], true, &mut cloned_output);
+        [
+            (output, transcoder.replaced()),
+            (cloned_output, cloned.replaced()),
+        ]
+    }
+
+    #[test]
+    fn cloned() {
+        let txt = "Disallow: /фу/日本/😀\n";
+        let labels = ["utf-16le", "utf-16be", "shift_jis", "euc-jp", "gb18030"];
+        let labels = labels
+            .into_iter()
+            .chain(["big5", "iso-2022-jp", "replacement"]);
+        for label in labels.chain(["windows-1251"]) {
+            let charset = Charset::for_label(label).unwrap();
+            let Codec::Encoding(encoding) = charset.codec else {
+                unreachable!();
+            };
+
+            let (encoded, _, _) = encoding.encode(txt);
+            let mut malformed = encoded.to_vec();
+            malformed.extend_from_slice(b"\x81\n\x1b$B\x1b(\xd8\x00\xdc");
+            malformed.extend_from_slice(&encoded);
+            for txt in [encoded.as_ref(), malformed.as_slice()] {
+                for at in 0..=txt.len() {
+                    let [output, cloned] = split(charset, txt, at);
+                    assert_eq!(output, cloned, "{label} at {at}");
+                }
+            }
+        }
+
+        let charset = Charset::for_label("utf-16le").unwrap();
+        let [(output, _), _] = split(charset, &utf16(txt, false)[2..], 25);
+        assert_eq!(output, txt.as_bytes());
+    }
+}
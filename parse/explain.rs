@@ -0,0 +1,107 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::parse::Rule;
+
+/// The `Reason` enum represents the source of the access decision.
+/// See [crate::Robots::explain].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason<'a> {
+    /// The path matched the rule with the highest precedence.
+    Matched(&'a Rule),
+    /// None of the rules matched the path.
+    /// The path is allowed by default.
+    Unmatched,
+    /// The `/robots.txt` path is always allowed.
+    RobotsTxt,
+    /// The site is fully allowed or disallowed.
+    /// See [crate::Robots::is_always].
+    Always,
+}
+
+impl<'a> Reason<'a> {
+    /// Returns the matched rule (if any).
+    pub fn rule(&self) -> Option<&'a Rule> {
+        match self {
+            Self::Matched(rule) => Some(rule),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the decision was not made by any rule.
+    pub fn is_default(&self) -> bool {
+        !matches!(self, Self::Matched(_))
+    }
+}
+
+/// The `Explanation` struct represents the access decision
+/// with the reason it was made for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Explanation<'a> {
+    allowed: bool,
+    user_agent: &'a str,
+    reason: Reason<'a>,
+}
+
+impl<'a> Explanation<'a> {
+    /// Creates a new `Explanation` with the specified decision.
+    pub fn new(allowed: bool, user_agent: &'a str, reason: Reason<'a>) -> Self {
+        Self {
+            allowed,
+            user_agent,
+            reason,
+        }
+    }
+
+    /// Returns true if the path is allowed.
+    pub fn is_allowed(&self) -> bool {
+        self.allowed
+    }
+
+    /// Returns the user-agent of the group the decision came from.
+    pub fn user_agent(&self) -> &'a str {
+        self.user_agent
+    }
+
+    /// Returns the reason of the decision.
+    pub fn reason(&self) -> Reason<'a> {
+        self.reason
+    }
+
+    /// Returns the matched rule (if any).
+    pub fn rule(&self) -> Option<&'a Rule> {
+        self.reason.rule()
+    }
+
+    /// Returns true if the decision was not made by any rule.
+    pub fn is_default(&self) -> bool {
+        self.reason.is_default()
+    }
+}
+
+impl Display for Explanation<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let decision = match self.allowed {
+            true => "allowed",
+            false => "disallowed",
+        };
+
+        write!(f, "{decision} for user-agent `{}`: ", self.user_agent)?;
+        match self.reason {
+            Reason::Matched(rule) => {
+                let directive = match rule.is_allowed() {
+                    true => "Allow",
+                    false => "Disallow",
+                };
+
+                write!(f, "matched `{directive}: {}`", rule.pattern())?;
+                match rule.position() {
+                    Some(position) => write!(f, " on line {}", position.line()),
+                    None => Ok(()),
+                }
+            }
+            Reason::Unmatched => write!(f, "no matching rules"),
+            Reason::RobotsTxt => write!(f, "robots.txt is always allowed"),
+            Reason::Always => write!(f, "the site is fully {decision}"),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use url::{Host as UrlHost, Url};
+
+/// The `Host` struct represents the Yandex `host` directive,
+/// i.e. the preferred mirror of the site.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Host {
+    scheme: Option<String>,
+    host: UrlHost,
+    port: Option<u16>,
+}
+
+impl Host {
+    /// Returns the scheme of the mirror (if specified).
+    /// NOTE: Either `http` or `https`.
+    pub fn scheme(&self) -> Option<&str> {
+        self.scheme.as_deref()
+    }
+
+    /// Returns the host of the mirror.
+    pub fn host(&self) -> &UrlHost {
+        &self.host
+    }
+
+    /// Returns the port of the mirror (if specified).
+    pub fn port(&self) -> Option<u16> {
+        self.port
+    }
+
+    /// Returns the URL moved onto the mirror.
+    /// NOTE: The scheme is kept unless specified by the mirror.
+    pub(crate) fn rewrite(&self, url: &Url) -> Url {
+        let mut url = url.clone();
+        if let Some(scheme) = &self.scheme {
+            // Switching between special schemes never fails.
+            let _ = url.set_scheme(scheme);
+        }
+
+        // Both fail only for the URLs without a host.
+        let host = self.host.to_string();
+        let _ = url.set_host(Some(host.as_str()));
+        let _ = url.set_port(self.port);
+        url
+    }
+}
+
+impl Display for Host {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if let Some(scheme) = &self.scheme {
+            write!(f, "{scheme}://")?;
+        }
+
+        write!(f, "{}", self.host)?;
+        if let Some(port) = self.port {
+            write!(f, ":{port}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses the `host` value, i.e. `[scheme://]host[:port]`, into the `Host`.
+pub(crate) fn parse_host(u: &[u8]) -> Option<Host> {
+    let u = std::str::from_utf8(u).ok()?;
+    let u = u.trim();
+    let u = u.strip_suffix('/').unwrap_or(u);
+
+    // The host is lowercased while parsed.
+    let (scheme, u) = match u.split_once("://") {
+        Some((scheme, u)) if scheme.eq_ignore_ascii_case("http") => (Some("http"), u),
+        Some((scheme, u)) if scheme.eq_ignore_ascii_case("https") => (Some("https"), u),
+        Some(_) => return None,
+        None => (None, u),
+    };
+
+    let scheme = scheme.map(str::to_string);
+
+    if u.is_empty() || u.contains(['/', '?', '#', '@', ' ']) {
+        return None;
+    }
+
+    // The IPv6 address contains colons itself.
+    let (host, port) = match u.rfind(':') {
+        Some(i) if !u[i..].contains(']') => (&u[..i], Some(u[i + 1..].parse::<u16>().ok()?)),
+        _ => (u, None),
+    };
+
+    let host = UrlHost::parse(host).ok()?;
+    Some(Host { scheme, host, port })
+}
+
+#[cfg(test)]
+mod mirrors {
+    use super::*;
+
+    fn host(u: &str) -> Option<String> {
+        parse_host(u.as_bytes()).map(|h| h.to_string())
+    }
+
+    #[test]
+    fn parsing() {
+        assert_eq!(host("Example.com"), Some("example.com".to_string()));
+        assert_eq!(
+            host("example.com:8080"),
+            Some("example.com:8080".to_string())
+        );
+        let u = Some("https://example.com".to_string());
+        assert_eq!(host("https://example.com/"), u);
+        assert_eq!(host("HTTPS://Example.COM"), u);
+        assert_eq!(host("[::1]:80"), Some("[::1]:80".to_string()));
+        assert_eq!(host("127.0.0.1"), Some("127.0.0.1".to_string()));
+
+        assert_eq!(host(""), None);
+        assert_eq!(host("ftp://example.com"), None);
+        assert_eq!(host("example.com/a"), None);
+        assert_eq!(host("example.com:port"), None);
+        assert_eq!(host("example.com:99999"), None);
+        assert_eq!(host("foo@example.com"), None);
+    }
+
+    #[test]
+    fn rewrite() {
+        let u = Url::parse("http://www.example.com:8080/a?b#c").unwrap();
+
+        let h = parse_host(b"example.com").unwrap();
+        assert_eq!(h.rewrite(&u).as_str(), "http://example.com/a?b#c");
+
+        let h = parse_host(b"https://example.com:8443").unwrap();
+        assert_eq!(h.rewrite(&u).as_str(), "https://example.com:8443/a?b#c");
+    }
+}
@@ -0,0 +1,302 @@
+use std::collections::HashMap;
+use std::mem::replace;
+
+use aho_corasick::AhoCorasick;
+
+use crate::parse::Rule;
+
+/// The `Node` struct represents the single byte of the literal prefix.
+#[derive(Debug, Clone, Default)]
+struct Node {
+    children: Vec<(u8, usize)>,
+    plain: Option<usize>,
+    wildcards: Vec<usize>,
+}
+
+/// The `Lookup` struct holds the buffers reused between the lookups.
+#[derive(Debug, Default)]
+pub(crate) struct Lookup {
+    // The wildcard rules that may match the path.
+    candidates: Vec<usize>,
+    // The number of segments of every rule found in the path.
+    found: Vec<usize>,
+    // The segments found in the path.
+    seen: Vec<bool>,
+    touched: Vec<usize>,
+}
+
+/// The `RuleIndex` struct provides the lookup of the highest-precedence
+/// matching rule in time roughly proportional to the path length.
+///
+/// Rules are stored in the prefix trie by their literal prefix, i.e.
+/// the whole pattern for the plain rules and the part before the first
+/// wildcard otherwise. The plain rule matches if its node is on the path
+/// walk, the wildcard rules found on the walk are only candidates and are
+/// evaluated unless the already found rule takes precedence over them.
+///
+/// Wildcard rules with literal segments after the prefix, e.g. `.php`
+/// of `/*.php$`, are stored in the combined automaton of all segments
+/// instead, and become candidates once all of their segments are found
+/// in the path in a single pass.
+#[derive(Debug, Clone)]
+pub(crate) struct RuleIndex {
+    nodes: Vec<Node>,
+    automaton: Option<AhoCorasick>,
+    // The rules of every segment of the automaton.
+    owners: Vec<Vec<usize>>,
+    // The number of segments of every rule.
+    required: Vec<usize>,
+}
+
+impl RuleIndex {
+    /// Creates a new `RuleIndex` of the rules sorted by precedence.
+    pub fn new(rules: &[Rule]) -> Self {
+        let mut index = Self {
+            nodes: vec![Node::default()],
+            automaton: None,
+            owners: Vec::new(),
+            required: vec![0; rules.len()],
+        };
+
+        let mut segments = HashMap::new();
+        for (id, rule) in rules.iter().enumerate() {
+            if rule.is_unmatchable() {
+                continue;
+            }
+
+            let literal = rule.literal_segments();
+            let node = index.insert(rule.literal_prefix().as_bytes());
+            let node = &mut index.nodes[node];
+            match rule.is_plain() {
+                // Equal patterns are sorted by permission.
+                true if node.plain.is_none() => node.plain = Some(id),
+                true => {}
+                false if literal.is_empty() => node.wildcards.push(id),
+                false => index.required[id] = literal.len(),
+            }
+
+            for segment in literal {
+                let next = segments.len();
+                let segment = *segments.entry(segment).or_insert(next);
+                match index.owners.get_mut(segment) {
+                    Some(owners) => owners.push(id),
+                    None => index.owners.push(vec![id]),
+                }
+            }
+        }
+
+        if !segments.is_empty() {
+            let mut segments: Vec<_> = segments.into_iter().collect();
+            segments.sort_unstable_by_key(|(_, u)| *u);
+            let segments = segments.into_iter().map(|(u, _)| u);
+            index.automaton = AhoCorasick::new(segments).ok();
+        }
+
+        index
+    }
+
+    /// Inserts the prefix into the trie and returns its node.
+    fn insert(&mut self, prefix: &[u8]) -> usize {
+        let mut current = 0;
+        for byte in prefix {
+            let children = &self.nodes[current].children;
+            current = match children.binary_search_by_key(byte, |(u, _)| *u) {
+                Ok(i) => children[i].1,
+                Err(i) => {
+                    let next = self.nodes.len();
+                    self.nodes[current].children.insert(i, (*byte, next));
+                    self.nodes.push(Node::default());
+                    next
+                }
+            };
+        }
+
+        current
+    }
+
+    /// Returns the child of the node with the specified byte (if any).
+    fn child(&self, node: usize, byte: u8) -> Option<usize> {
+        let children = &self.nodes[node].children;
+        let i = children.binary_search_by_key(&byte, |(u, _)| *u).ok()?;
+        Some(children[i].1)
+    }
+
+    /// Collects the rules the literal prefix and all segments of which
+    /// are found in the path.
+    /// NOTE: Every rule is a candidate if the automaton failed to build.
+    fn collect(&self, rules: &[Rule], path: &str, lookup: &mut Lookup) {
+        let automaton = match &self.automaton {
+            Some(automaton) => automaton,
+            None => {
+                let rules = self.required.iter().enumerate();
+                let rules = rules.filter(|(_, u)| **u > 0).map(|(id, _)| id);
+                lookup.candidates.extend(rules);
+                return;
+            }
+        };
+
+        lookup
+            .found
+            .resize(lookup.found.len().max(self.required.len()), 0);
+        lookup
+            .seen
+            .resize(lookup.seen.len().max(self.owners.len()), false);
+        for matched in automaton.find_overlapping_iter(path) {
+            let segment = matched.pattern().as_usize();
+            if replace(&mut lookup.seen[segment], true) {
+                continue;
+            }
+
+            lookup.touched.push(segment);
+            for id in self.owners[segment].iter().copied() {
+                lookup.found[id] += 1;
+                let prefix = rules[id].literal_prefix();
+                if lookup.found[id] == self.required[id] && path.starts_with(prefix) {
+                    lookup.candidates.push(id);
+                }
+            }
+        }
+
+        // Resets the buffers for the next lookup.
+        for segment in lookup.touched.drain(..) {
+            lookup.seen[segment] = false;
+            for id in self.owners[segment].iter().copied() {
+                lookup.found[id] = 0;
+            }
+        }
+    }
+
+    /// Returns the position of the highest-precedence rule matching
+    /// the normalized path within the rules the index was built from.
+    /// NOTE: Wildcard candidates are collected into the lookup buffers.
+    pub fn find_with(&self, rules: &[Rule], path: &str, lookup: &mut Lookup) -> Option<usize> {
+        lookup.candidates.clear();
+
+        let mut best: Option<usize> = None;
+        let mut current = Some(0);
+        let mut bytes = path.bytes();
+        while let Some(node) = current {
+            if let Some(id) = self.nodes[node].plain {
+                best = Some(best.map_or(id, |u| u.min(id)));
+            }
+
+            let wildcards = self.nodes[node].wildcards.iter();
+            lookup.candidates.extend(wildcards.copied());
+            current = bytes.next().and_then(|u| self.child(node, u));
+        }
+
+        self.collect(rules, path, lookup);
+
+        // Only rules preceding the best plain rule can take over.
+        let candidates = &mut lookup.candidates;
+        candidates.sort_unstable();
+        let candidates = candidates
+            .iter()
+            .take_while(|u| best.is_none_or(|b| **u < b));
+        let matched = candidates.copied().find(|u| rules[*u].is_match(path));
+        matched.or(best)
+    }
+
+    /// Returns the position of the highest-precedence rule matching
+    /// the normalized path within the rules the index was built from.
+    #[cfg(test)]
+    pub fn find(&self, rules: &[Rule], path: &str) -> Option<usize> {
+        self.find_with(rules, path, &mut Lookup::default())
+    }
+}
+
+#[cfg(test)]
+mod lookup {
+    use super::*;
+
+    /// Returns the deterministic sequence of pseudo-random numbers.
+    fn random(seed: u64) -> impl Iterator<Item = u64> {
+        let next = |u: &u64| Some(u.wrapping_mul(6364136223846793005).wrapping_add(1));
+        std::iter::successors(Some(seed), next)
+            .skip(1)
+            .map(|u| u >> 33)
+    }
+
+    /// Returns the string of the specified alphabet and length.
+    fn generate(random: &mut impl Iterator<Item = u64>, alphabet: &[u8], len: u64) -> String {
+        let len = random.next().unwrap() % len + 1;
+        let u = (0..len).map(|_| alphabet[random.next().unwrap() as usize % alphabet.len()]);
+        String::from_utf8(u.collect()).unwrap()
+    }
+
+    #[test]
+    fn plain() {
+        let mut rules = vec![Rule::new("/a", false), Rule::new("/a/b", true)];
+        rules.sort();
+
+        let index = RuleIndex::new(&rules);
+        assert_eq!(index.find(&rules, "/a/b/c"), Some(0));
+        assert_eq!(index.find(&rules, "/a/c"), Some(1));
+        assert_eq!(index.find(&rules, "/b"), None);
+    }
+
+    #[test]
+    fn wildcards() {
+        let mut rules = vec![
+            Rule::new("/a", true),
+            Rule::new("/*.php$", false),
+            Rule::new("/a$", false),
+            Rule::new("/a$b", false),
+        ];
+
+        rules.sort();
+
+        let index = RuleIndex::new(&rules);
+        let find = |u| index.find(&rules, u).map(|u| rules[u].pattern());
+        assert_eq!(find("/a/b.php"), Some("/*.php$"));
+        assert_eq!(find("/a"), Some("/a$"));
+        assert_eq!(find("/ab"), Some("/a"));
+        assert_eq!(find("/b.php5"), None);
+    }
+
+    #[test]
+    fn many_wildcards() {
+        let rules = (0..1000).map(|u| match u % 3 {
+            0 => Rule::new(&format!("/*/{u}.php$"), false),
+            1 => Rule::new(&format!("/*q={u}*"), false),
+            _ => Rule::new(&format!("/a{u}*b"), true),
+        });
+
+        let mut rules: Vec<_> = rules.collect();
+        rules.sort();
+
+        let index = RuleIndex::new(&rules);
+        let mut lookup = Lookup::default();
+        for path in ["/x/300.php", "/search?q=301&p=1", "/a302/b", "/x/303.php5"] {
+            let expected = rules.iter().position(|r| r.is_match(path));
+            assert_eq!(
+                index.find_with(&rules, path, &mut lookup),
+                expected,
+                "{path}"
+            );
+            assert!(lookup.candidates.len() <= 3, "{path}");
+        }
+    }
+
+    #[test]
+    fn equivalence() {
+        let mut random = random(42);
+        for _ in 0..200 {
+            let count = random.next().unwrap() % 20;
+            let rules = (0..count).map(|_| {
+                let pattern = generate(&mut random, b"/ab*$", 6);
+                Rule::new(pattern.as_str(), random.next().unwrap() & 1 == 0)
+            });
+
+            let mut rules: Vec<_> = rules.collect();
+            rules.sort();
+
+            let index = RuleIndex::new(&rules);
+            for _ in 0..50 {
+                let path = "/".to_string() + &generate(&mut random, b"/ab", 8);
+                let expected = rules.iter().position(|r| r.is_match(path.as_str()));
+                assert_eq!(index.find(&rules, path.as_str()), expected, "{path}");
+            }
+        }
+    }
+}
@@ -1,17 +1,18 @@
 use bstr::ByteSlice;
-use nom::branch::{alt, Alt};
+use nom::branch::alt;
 use nom::bytes::complete::{tag, tag_no_case, take_while};
 use nom::character::complete::{space0, space1};
-use nom::combinator::{eof, opt};
-use nom::error::{Error as NomError, ParseError as NomParseError};
-use nom::multi::many_till;
+use nom::combinator::opt;
+use nom::error::{Error as NomError, ErrorKind as NomErrorKind};
 use nom::sequence::preceded;
 use nom::{Err as NomErr, IResult as NomResult};
 
 use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::ops::Range;
+
+use crate::parse::{Dialect, Keyword};
 
 /// The `Directive` enum represents every supported `robots.txt` directive.
-// TODO: Attach position.
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum Directive<'a> {
     UserAgent(&'a [u8]),
@@ -19,6 +20,10 @@ pub enum Directive<'a> {
     Disallow(&'a [u8]),
     CrawlDelay(&'a [u8]),
     Sitemap(&'a [u8]),
+    CleanParam(&'a [u8]),
+    Host(&'a [u8]),
+    RequestRate(&'a [u8]),
+    VisitTime(&'a [u8]),
     Unknown(&'a [u8]),
 }
 
@@ -30,42 +35,179 @@ impl Debug for Directive<'_> {
             Self::Disallow(v) => f.debug_tuple("Disallow").field(&v.as_bstr()).finish(),
             Self::CrawlDelay(v) => f.debug_tuple("Crawl-Delay").field(&v.as_bstr()).finish(),
             Self::Sitemap(v) => f.debug_tuple("Sitemap").field(&v.as_bstr()).finish(),
+            Self::CleanParam(v) => f.debug_tuple("Clean-Param").field(&v.as_bstr()).finish(),
+            Self::Host(v) => f.debug_tuple("Host").field(&v.as_bstr()).finish(),
+            Self::RequestRate(v) => f.debug_tuple("Request-Rate").field(&v.as_bstr()).finish(),
+            Self::VisitTime(v) => f.debug_tuple("Visit-Time").field(&v.as_bstr()).finish(),
             Self::Unknown(v) => f.debug_tuple("Unknown").field(&v.as_bstr()).finish(),
         }
     }
 }
 
+/// The `Span` struct represents the byte range in the original input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    start: usize,
+    end: usize,
+}
+
+impl Span {
+    /// Creates a new `Span` with the specified bounds.
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// Creates a new `Span` of the subslice within the input slice.
+    /// NOTE: Expects the subslice to be a part of the input slice.
+    fn within(input: &[u8], subslice: &[u8]) -> Self {
+        let start = subslice.as_ptr() as usize - input.as_ptr() as usize;
+        Self::new(start, start + subslice.len())
+    }
+
+    /// Returns the inclusive start of the span.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Returns the exclusive end of the span.
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// Returns the length of the span in bytes.
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Returns true if the span is empty.
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Returns the span as a range.
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+/// The `Position` struct represents the location of the directive
+/// in the original input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Position {
+    line: usize,
+    offset: usize,
+    key: Span,
+    value: Span,
+}
+
+impl Position {
+    /// Creates a new `Position` with the specified line, offset and spans.
+    pub fn new(line: usize, offset: usize, key: Span, value: Span) -> Self {
+        Self {
+            line,
+            offset,
+            key,
+            value,
+        }
+    }
+
+    /// Returns the one-based line number.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the byte offset of the line start.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the span of the directive key.
+    /// NOTE: Empty for the unknown directives.
+    pub fn key(&self) -> Span {
+        self.key
+    }
+
+    /// Returns the span of the directive value.
+    pub fn value(&self) -> Span {
+        self.value
+    }
+}
+
+/// The `Token` struct represents the directive and its position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    directive: Directive<'a>,
+    position: Position,
+}
+
+impl<'a> Token<'a> {
+    /// Creates a new `Token` with the specified directive and position.
+    pub fn new(directive: Directive<'a>, position: Position) -> Self {
+        Self {
+            directive,
+            position,
+        }
+    }
+
+    /// Returns the directive.
+    pub fn directive(&self) -> &Directive<'a> {
+        &self.directive
+    }
+
+    /// Returns the position of the directive.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+}
+
+impl<'a> From<Directive<'a>> for Token<'a> {
+    fn from(directive: Directive<'a>) -> Self {
+        Self::new(directive, Position::default())
+    }
+}
+
 const CARRIAGE: u8 = b'\r';
 const NEWLINE: u8 = b'\n';
 const COMMENT: u8 = b'#';
+const NULL: u8 = b'\x00';
 
-///
+/// Returns true if the character is not a line ending.
+/// NOTE: Null characters are treated as line endings.
 pub fn b_not_line_ending(c: u8) -> bool {
-    c != NEWLINE && c != CARRIAGE
+    c != NEWLINE && c != CARRIAGE && c != NULL
 }
 
-///
+/// Returns true if the character is neither a line ending nor a comment.
 pub fn b_not_line_ending_or_comment(c: u8) -> bool {
-    c != NEWLINE && c != CARRIAGE && c != COMMENT
+    b_not_line_ending(c) && c != COMMENT
 }
 
-///
+/// Consumes the line ending (if any).
 pub fn b_consume_newline(input: &[u8]) -> NomResult<&[u8], Option<&[u8]>> {
-    let (input, _) = take_while(|i| i == CARRIAGE)(input)?;
-    let (input, output) = opt(tag(b"\n"))(input)?;
-    Ok((input, output))
+    opt(alt((tag(b"\r\n"), tag(b"\n"), tag(b"\r"), tag(b"\x00"))))(input)
 }
 
-///
-fn builder<'a, O, E: NomParseError<&'a [u8]>>(
-    input: &'a [u8],
-    spellings: impl Alt<&'a [u8], O, E>,
-) -> NomResult<&'a [u8], &'a [u8]>
-where
-    NomErr<NomError<&'a [u8]>>: From<NomErr<E>>,
-{
+/// The directive with the raw key and the raw value slices.
+type Lexeme<'a> = (Directive<'a>, &'a [u8], &'a [u8]);
+
+/// Matches the key from the spelling list (case-insensitive).
+fn spelling<'a>(input: &'a [u8], spellings: &[&str]) -> NomResult<&'a [u8], &'a [u8]> {
+    for u in spellings {
+        if let Ok(matched) = tag_no_case::<_, _, NomError<_>>(*u)(input) {
+            return Ok(matched);
+        }
+    }
+
+    Err(NomErr::Error(NomError::new(input, NomErrorKind::Tag)))
+}
+
+/// Parses the key-value pair with the key from the spelling list.
+fn builder<'a>(input: &'a [u8], spellings: &[&str]) -> NomResult<&'a [u8], (&'a [u8], &'a [u8])> {
     // Tries to match to the spelling list.
-    let (input, _) = preceded(space0, alt(spellings))(input)?;
+    let (input, _) = space0(input)?;
+    let (input, key) = spelling(input, spellings)?;
     // Tries to match the separator (colon or spaces).
     let (input, _) = alt((preceded(space0, tag(b":")), space1))(input)?;
     // Tries to retrieve the value of the kv pair.
@@ -76,102 +218,171 @@ where
     let (input, _) = b_consume_newline(input)?;
 
     let line = line.trim();
-    Ok((input, line))
+    Ok((input, (key, line)))
 }
 
 /// Attempts to parse the `user-agent` directive.
-fn user_agent(input: &[u8]) -> NomResult<&[u8], Directive> {
-    let matcher = (
-        tag_no_case("user-agent"),
-        tag_no_case("user agent"),
-        tag_no_case("useragent"),
-    );
-
-    let (input, agent) = builder(input, matcher)?;
-    Ok((input, Directive::UserAgent(agent)))
+fn user_agent(input: &[u8], dialect: Dialect) -> NomResult<&[u8], Lexeme<'_>> {
+    let matcher = dialect.spellings(Keyword::UserAgent);
+    let (input, (key, agent)) = builder(input, matcher)?;
+    Ok((input, (Directive::UserAgent(agent), key, agent)))
 }
 
 /// Attempts to parse the `allow` directive.
-fn allow(input: &[u8]) -> NomResult<&[u8], Directive> {
-    let matcher = (
-        tag_no_case("allow"),
-        tag_no_case("alow"),
-        tag_no_case("allaw"),
-    );
-
-    let (input, rule) = builder(input, matcher)?;
-    Ok((input, Directive::Allow(rule)))
+fn allow(input: &[u8], dialect: Dialect) -> NomResult<&[u8], Lexeme<'_>> {
+    let matcher = dialect.spellings(Keyword::Allow);
+    let (input, (key, rule)) = builder(input, matcher)?;
+    Ok((input, (Directive::Allow(rule), key, rule)))
 }
 
 /// Attempts to parse the `disallow` directive.
-fn disallow(input: &[u8]) -> NomResult<&[u8], Directive> {
-    let matcher = (
-        tag_no_case("disallow"),
-        tag_no_case("dissallow"),
-        tag_no_case("dissalow"),
-        tag_no_case("disalow"),
-        tag_no_case("diasllow"),
-        tag_no_case("disallaw"),
-    );
+fn disallow(input: &[u8], dialect: Dialect) -> NomResult<&[u8], Lexeme<'_>> {
+    let matcher = dialect.spellings(Keyword::Disallow);
 
     // Empty disallow is equivalent to allow all.
     // https://moz.com/learn/seo/robotstxt
-    let (input, rule) = builder(input, matcher)?;
+    let (input, (key, rule)) = builder(input, matcher)?;
     match rule.is_empty() {
-        true => Ok((input, Directive::Allow(b"/"))),
-        false => Ok((input, Directive::Disallow(rule))),
+        true => Ok((input, (Directive::Allow(b"/"), key, rule))),
+        false => Ok((input, (Directive::Disallow(rule), key, rule))),
     }
 }
 
 /// Attempts to parse the `crawl-delay` directive.
-fn crawl_delay(input: &[u8]) -> NomResult<&[u8], Directive> {
-    let matcher = (
-        tag_no_case("crawl-delay"),
-        tag_no_case("crawl delay"),
-        tag_no_case("crawldelay"),
-    );
+fn crawl_delay(input: &[u8], dialect: Dialect) -> NomResult<&[u8], Lexeme<'_>> {
+    let matcher = dialect.spellings(Keyword::CrawlDelay);
+    let (input, (key, delay)) = builder(input, matcher)?;
+    Ok((input, (Directive::CrawlDelay(delay), key, delay)))
+}
 
-    let (input, delay) = builder(input, matcher)?;
-    Ok((input, Directive::CrawlDelay(delay)))
+/// Attempts to parse the `request-rate` directive.
+fn request_rate(input: &[u8], dialect: Dialect) -> NomResult<&[u8], Lexeme<'_>> {
+    let matcher = dialect.spellings(Keyword::RequestRate);
+    let (input, (key, rate)) = builder(input, matcher)?;
+    Ok((input, (Directive::RequestRate(rate), key, rate)))
+}
+
+/// Attempts to parse the `visit-time` directive.
+fn visit_time(input: &[u8], dialect: Dialect) -> NomResult<&[u8], Lexeme<'_>> {
+    let matcher = dialect.spellings(Keyword::VisitTime);
+    let (input, (key, time)) = builder(input, matcher)?;
+    Ok((input, (Directive::VisitTime(time), key, time)))
 }
 
 /// Attempts to parse the `sitemap` directive.
-fn sitemap(input: &[u8]) -> NomResult<&[u8], Directive> {
-    let matcher = (
-        tag_no_case("sitemap"),
-        tag_no_case("site-map"),
-        tag_no_case("site map"),
-    );
+fn sitemap(input: &[u8], dialect: Dialect) -> NomResult<&[u8], Lexeme<'_>> {
+    let matcher = dialect.spellings(Keyword::Sitemap);
+    let (input, (key, sitemap)) = builder(input, matcher)?;
+    Ok((input, (Directive::Sitemap(sitemap), key, sitemap)))
+}
+
+/// Attempts to parse the `clean-param` directive.
+fn clean_param(input: &[u8], dialect: Dialect) -> NomResult<&[u8], Lexeme<'_>> {
+    let matcher = dialect.spellings(Keyword::CleanParam);
+    let (input, (key, params)) = builder(input, matcher)?;
+    Ok((input, (Directive::CleanParam(params), key, params)))
+}
 
-    let (input, sitemap) = builder(input, matcher)?;
-    Ok((input, Directive::Sitemap(sitemap)))
+/// Attempts to parse the `host` directive.
+fn host(input: &[u8], dialect: Dialect) -> NomResult<&[u8], Lexeme<'_>> {
+    let matcher = dialect.spellings(Keyword::Host);
+    let (input, (key, host)) = builder(input, matcher)?;
+    Ok((input, (Directive::Host(host), key, host)))
 }
 
 /// Consumes the line as no directives were found here.
-fn unknown(input: &[u8]) -> NomResult<&[u8], Directive> {
+fn unknown(input: &[u8]) -> NomResult<&[u8], Lexeme<'_>> {
     let (input, unknown) = take_while(b_not_line_ending)(input)?;
     let (input, _) = b_consume_newline(input)?;
-    Ok((input, Directive::Unknown(unknown)))
+    Ok((input, (Directive::Unknown(unknown), &unknown[..0], unknown)))
+}
+
+/// Parses the single line including its ending (if any).
+fn lexeme(input: &[u8], dialect: Dialect) -> NomResult<&[u8], Lexeme<'_>> {
+    alt((
+        |u| user_agent(u, dialect),
+        |u| allow(u, dialect),
+        |u| disallow(u, dialect),
+        |u| crawl_delay(u, dialect),
+        |u| request_rate(u, dialect),
+        |u| visit_time(u, dialect),
+        |u| sitemap(u, dialect),
+        |u| clean_param(u, dialect),
+        |u| host(u, dialect),
+        unknown,
+    ))(input)
+}
+
+/// Removes the byte order mark (BOM) if any.
+/// NOTE: Every byte of the mark is optional.
+pub(crate) fn b_strip_bom(input: &[u8]) -> &[u8] {
+    let mut input = input;
+    for u in b"\xef\xbb\xbf" {
+        input = input.strip_prefix(&[*u]).unwrap_or(input);
+    }
+
+    input
+}
+
+/// Parses the single line into the token with the specified line number
+/// and the byte offset of the line within the original input.
+/// NOTE: Expects the line to contain at most one line ending.
+pub(crate) fn into_token(input: &[u8], line: usize, offset: usize, dialect: Dialect) -> Token<'_> {
+    // Discards the possibility of any error as `unknown` consumes anything.
+    let (directive, key, value) = match lexeme(input, dialect) {
+        Ok((_, lexeme)) => lexeme,
+        Err(_) => unreachable!(),
+    };
+
+    let shift = |u: Span| Span::new(u.start + offset, u.end + offset);
+    let key = shift(Span::within(input, key));
+    let value = shift(Span::within(input, value));
+    Token::new(directive, Position::new(line, offset, key, value))
 }
 
 /// Parses the input slice into the list of directives.
-fn lex(input: &[u8]) -> NomResult<&[u8], Vec<Directive>> {
-    // Removes the byte order mark (BOM).
-    let (input, _) = opt(tag(b"\xef"))(input)?;
-    let (input, _) = opt(tag(b"\xbb"))(input)?;
-    let (input, _) = opt(tag(b"\xbf"))(input)?;
+fn lex(input: &[u8], dialect: Dialect) -> NomResult<&[u8], Vec<Token<'_>>> {
+    let origin = input;
+    let input = b_strip_bom(input);
 
-    // Creates and runs the matcher.
-    let matcher = alt((user_agent, allow, disallow, crawl_delay, sitemap, unknown));
-    let (input, (directives, _)) = many_till(matcher, eof)(input)?;
+    let mut directives = Vec::new();
+    let mut input = input;
+
+    // Every iteration consumes exactly one line.
+    while !input.is_empty() {
+        let offset = origin.len() - input.len();
+        let (rest, (directive, key, value)) = lexeme(input, dialect)?;
+
+        let key = Span::within(origin, key);
+        let value = Span::within(origin, value);
+        let position = Position::new(directives.len() + 1, offset, key, value);
+
+        directives.push(Token::new(directive, position));
+        input = rest;
+    }
 
     Ok((input, directives))
 }
 
-/// Parses the input slice into the list of directives.
-pub fn into_directives(input: &[u8]) -> Vec<Directive> {
+/// Parses the input slice into the list of directives
+/// accepting the key spellings of the default dialect.
+///
+/// ```rust
+/// use robotxt::{into_directives, Directive};
+///
+/// let tokens = into_directives(b"User-Agent: foobot\nDisallow: /");
+/// assert!(matches!(tokens[1].directive(), Directive::Disallow(b"/")));
+/// assert_eq!(tokens[1].position().line(), 2);
+/// ```
+pub fn into_directives(input: &[u8]) -> Vec<Token<'_>> {
+    into_directives_with(input, Dialect::default())
+}
+
+/// Parses the input slice into the list of directives
+/// accepting the key spellings of the specified dialect.
+pub fn into_directives_with(input: &[u8], dialect: Dialect) -> Vec<Token<'_>> {
     // Discards the possibility of any error as `unknown` consumes anything.
-    match lex(input) {
+    match lex(input, dialect) {
         Ok((_, directives)) => directives,
         Err(_) => unreachable!(), // TODO: Verify soundness.
     }
@@ -181,10 +392,15 @@ pub fn into_directives(input: &[u8]) -> Vec<Directive> {
 mod parsing {
     use super::*;
 
+    fn directives(input: &[u8]) -> Vec<Directive<'_>> {
+        let r = into_directives(input).into_iter();
+        r.map(|t| *t.directive()).collect()
+    }
+
     #[test]
     fn single() {
         let r = b"user-agent: robotxt";
-        let r = into_directives(r);
+        let r = directives(r);
 
         let ua = b"robotxt";
         let ua = Directive::UserAgent(ua);
@@ -196,7 +412,7 @@ mod parsing {
         let r = b"
             user-agent: robotxt\n
             user-agent: robotxt";
-        let r = into_directives(r);
+        let r = directives(r);
 
         let ua = b"robotxt";
         let ua = Directive::UserAgent(ua);
@@ -204,3 +420,82 @@ mod parsing {
         assert_eq!(r, vec![em, ua, em, ua]);
     }
 }
+
+#[cfg(test)]
+mod positions {
+    use super::*;
+
+    #[test]
+    fn spans() {
+        let r = b"\xef\xbb\xbfUser-Agent: robotxt\r\n  Disallow : /a # b\n\nfoo";
+        let r = into_directives(r);
+
+        let ua = r[0].position();
+        assert_eq!((ua.line(), ua.offset()), (1, 3));
+        assert_eq!(ua.key().range(), 3..13);
+        assert_eq!(ua.value().range(), 15..22);
+
+        let da = r[1].position();
+        assert_eq!((da.line(), da.offset()), (2, 24));
+        assert_eq!(da.key().range(), 26..34);
+        assert_eq!(da.value().range(), 37..39);
+
+        let em = r[2].position();
+        assert_eq!((em.line(), em.offset()), (3, 44));
+        assert!(em.key().is_empty() && em.value().is_empty());
+
+        let uk = r[3].position();
+        assert_eq!((uk.line(), uk.offset()), (4, 45));
+        assert_eq!(uk.value().range(), 45..48);
+    }
+
+    #[test]
+    fn endings() {
+        let r = b"allow: /a\rallow: /b\r\rallow: /c\x00allow: /d";
+        let r = into_directives(r);
+
+        let lines: Vec<_> = r.iter().map(|t| t.position().line()).collect();
+        assert_eq!(lines, vec![1, 2, 3, 4, 5]);
+        assert_eq!(*r[3].directive(), Directive::Allow(b"/c"));
+        assert_eq!(r[4].position().offset(), 31);
+    }
+}
+
+#[cfg(test)]
+mod dialects {
+    use super::*;
+
+    fn directives(input: &[u8], dialect: Dialect) -> Vec<Directive<'_>> {
+        let r = into_directives_with(input, dialect).into_iter();
+        r.map(|t| *t.directive()).collect()
+    }
+
+    #[test]
+    fn typos() {
+        let r = b"useragent: a\nalow: /b\ndissallow: /c\nsite-map: d";
+
+        let lenient = vec![
+            Directive::UserAgent(b"a"),
+            Directive::Allow(b"/b"),
+            Directive::Disallow(b"/c"),
+            Directive::Sitemap(b"d"),
+        ];
+        assert_eq!(directives(r, Dialect::Lenient), lenient);
+
+        let google = vec![
+            Directive::UserAgent(b"a"),
+            Directive::Unknown(b"alow: /b"),
+            Directive::Disallow(b"/c"),
+            Directive::Sitemap(b"d"),
+        ];
+        assert_eq!(directives(r, Dialect::Google), google);
+
+        let standard = vec![
+            Directive::Unknown(b"useragent: a"),
+            Directive::Unknown(b"alow: /b"),
+            Directive::Unknown(b"dissallow: /c"),
+            Directive::Unknown(b"site-map: d"),
+        ];
+        assert_eq!(directives(r, Dialect::Standard), standard);
+    }
+}
@@ -1,10 +1,43 @@
+mod access;
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+mod asynchronous;
+mod borrowed;
+mod clean;
+mod dialect;
+mod document;
+mod encoding;
+mod explain;
+mod host;
+mod index;
 mod lexer;
+mod options;
+mod origin;
 mod parser;
 mod rule;
 mod rules;
+mod schedule;
+mod stream;
 
-use lexer::*;
-use rule::*;
-use rules::*;
+pub(crate) use encoding::{decode, detect, Transcoder};
+pub(crate) use index::*;
+pub(crate) use lexer::*;
+pub(crate) use rule::*;
+pub use rules::*;
 
+pub use access::*;
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+pub use asynchronous::*;
+pub use borrowed::*;
+pub use clean::*;
+pub use dialect::*;
+pub use document::*;
+pub use encoding::Charset;
+pub use explain::*;
+pub use host::*;
+pub use lexer::{into_directives, into_directives_with, Directive, Position, Span, Token};
+pub use options::*;
+pub use origin::*;
 pub use parser::*;
+pub use rule::Rule;
+pub use schedule::*;
+pub use stream::*;
@@ -0,0 +1,142 @@
+use std::cmp::max;
+
+use crate::parse::{Charset, Dialect, BYTES_LIMIT};
+
+/// The `ParserOptions` struct represents the settings of the `robots.txt` parser.
+/// See [crate::Document::from_slice_with] and [crate::Robots::from_slice_with].
+///
+/// ```rust
+/// use robotxt::{ParserOptions, Robots};
+///
+/// let options = ParserOptions::default().limit(1 << 20).cut_at_line(true);
+/// let options = options.dialect(robotxt::Dialect::Google);
+/// let r = Robots::from_slice_with(b"User-Agent: *\nDisallow: /", "foobot", &options);
+/// assert!(!r.is_allowed("/example/nope.txt"));
+/// assert_eq!(r.truncated(), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParserOptions {
+    pub(crate) limit: usize,
+    pub(crate) cut_at_line: bool,
+    pub(crate) dialect: Dialect,
+    pub(crate) charset: Option<Charset>,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self {
+            limit: BYTES_LIMIT,
+            cut_at_line: false,
+            dialect: Dialect::default(),
+            charset: None,
+        }
+    }
+}
+
+impl ParserOptions {
+    /// Sets the file size limit in bytes.
+    /// NOTE: Can not be less than [BYTES_LIMIT] as required by
+    /// [Request for Comments: 9309](https://www.rfc-editor.org/rfc/rfc9309.html#section-2.5).
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = max(limit, BYTES_LIMIT);
+        self
+    }
+
+    /// Sets if the oversized file should be cut at the end of the last full line
+    /// instead of in the middle of the directive.
+    pub fn cut_at_line(mut self, cut_at_line: bool) -> Self {
+        self.cut_at_line = cut_at_line;
+        self
+    }
+
+    /// Sets the interpretation of the `robots.txt` file.
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Sets the character encoding of the input without the byte order mark,
+    /// e.g. from the `Content-Type` header. The input in any other than UTF-8
+    /// encoding is transcoded before parsing, the limit and all offsets
+    /// then refer to the transcoded input.
+    /// NOTE: The UTF-16 and UTF-32 byte order marks take precedence.
+    pub fn charset(mut self, charset: Charset) -> Self {
+        self.charset = Some(charset);
+        self
+    }
+
+    /// Sets the character encoding of the input from the `charset` parameter
+    /// of the `Content-Type` header value (if any). See [ParserOptions::charset].
+    pub fn content_type(mut self, value: &str) -> Self {
+        self.charset = Charset::from_content_type(value).or(self.charset);
+        self
+    }
+
+    /// Returns the byte the oversized input should be truncated at.
+    pub(crate) fn truncate_at(&self, robots: &[u8]) -> Option<usize> {
+        if robots.len() <= self.limit {
+            return None;
+        }
+
+        if !self.cut_at_line {
+            return Some(self.limit);
+        }
+
+        // Line endings are included into the preceding line.
+        let line = robots[..self.limit]
+            .iter()
+            .rposition(|u| b"\r\n\x00".contains(u));
+        Some(line.map(|u| u + 1).unwrap_or(0))
+    }
+
+    /// Limits the input without copying it.
+    /// Returns the byte the input was truncated at (if any).
+    pub(crate) fn prepare<'a>(&self, robots: &'a [u8]) -> (&'a [u8], Option<usize>) {
+        let truncated = self.truncate_at(robots);
+        (&robots[..truncated.unwrap_or(robots.len())], truncated)
+    }
+}
+
+#[cfg(test)]
+mod truncation {
+    use super::*;
+
+    fn oversized() -> Vec<u8> {
+        let mut r = b"User-Agent: *\n".to_vec();
+        r.extend(b"Disallow: /a\n".repeat(BYTES_LIMIT / 10));
+        r
+    }
+
+    #[test]
+    fn limit() {
+        let o = ParserOptions::default().limit(1);
+        assert_eq!(o.limit, BYTES_LIMIT);
+
+        let r = oversized();
+        let o = ParserOptions::default().limit(r.len());
+        assert_eq!(o.prepare(&r), (r.as_slice(), None));
+    }
+
+    #[test]
+    fn truncated() {
+        let r = oversized();
+
+        let o = ParserOptions::default();
+        let (prepared, truncated) = o.prepare(&r);
+        assert_eq!(truncated, Some(BYTES_LIMIT));
+        assert_eq!(prepared.len(), BYTES_LIMIT);
+
+        let o = o.cut_at_line(true);
+        let (prepared, truncated) = o.prepare(&r);
+        assert_eq!(truncated, Some(prepared.len()));
+        assert!(prepared.len() <= BYTES_LIMIT);
+        assert!(prepared.ends_with(b"Disallow: /a\n"));
+    }
+
+    #[test]
+    fn single_line() {
+        let r = b"#".repeat(BYTES_LIMIT + 1);
+        let o = ParserOptions::default().cut_at_line(true);
+        assert_eq!(o.prepare(&r), (&r[..0], Some(0)));
+    }
+}
@@ -0,0 +1,111 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use url::{Origin, Position as UrlPosition, Url};
+
+/// An error type indicating that the `Url` could not be checked.
+/// See [crate::Robots::is_allowed_url].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UrlError {
+    /// The URL has no path, e.g. `mailto:` or `data:` URL.
+    CannotBeABase,
+    /// The URL origin differs from the `robots.txt` origin.
+    Origin { expected: Origin, found: Origin },
+}
+
+impl Display for UrlError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self {
+            Self::CannotBeABase => write!(f, "url has no path"),
+            Self::Origin { expected, found } => write!(
+                f,
+                "url origin `{}` differs from robots.txt origin `{}`",
+                found.ascii_serialization(),
+                expected.ascii_serialization()
+            ),
+        }
+    }
+}
+
+impl Error for UrlError {}
+
+/// Serializes the `Origin` as its ASCII serialization, e.g. `https://example.com`.
+/// NOTE: Opaque origins can not be deserialized.
+#[cfg(feature = "serde")]
+pub(crate) mod serde_origin {
+    use serde::de::Error as DeError;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use url::{Origin, Url};
+
+    pub fn serialize<S: Serializer>(origin: &Option<Origin>, s: S) -> Result<S::Ok, S::Error> {
+        match origin {
+            Some(origin) => s.serialize_some(&origin.ascii_serialization()),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Origin>, D::Error> {
+        let origin = Option::<String>::deserialize(d)?;
+        let origin = origin.map(|u| Url::parse(u.as_str()).map(|u| u.origin()));
+        let origin = origin.transpose().map_err(DeError::custom)?;
+        match origin {
+            Some(origin) if !origin.is_tuple() => Err(DeError::custom("opaque origin")),
+            origin => Ok(origin),
+        }
+    }
+}
+
+/// Returns the path with the query of the URL.
+pub fn url_path(url: &Url) -> Result<&str, UrlError> {
+    if url.cannot_be_a_base() {
+        return Err(UrlError::CannotBeABase);
+    }
+
+    Ok(&url[UrlPosition::BeforePath..UrlPosition::AfterQuery])
+}
+
+/// Returns the path with the query of the URL if its origin
+/// matches the expected one (if any).
+pub(crate) fn url_path_within<'a>(
+    url: &'a Url,
+    origin: Option<&Origin>,
+) -> Result<&'a str, UrlError> {
+    match origin {
+        Some(expected) if !expected.eq(&url.origin()) => Err(UrlError::Origin {
+            expected: expected.clone(),
+            found: url.origin(),
+        }),
+        _ => url_path(url),
+    }
+}
+
+#[cfg(test)]
+mod extracting {
+    use super::*;
+
+    #[test]
+    fn path() {
+        let u = Url::parse("https://example.com/a/b?c=d#e").unwrap();
+        assert_eq!(url_path(&u), Ok("/a/b?c=d"));
+
+        let u = Url::parse("https://example.com").unwrap();
+        assert_eq!(url_path(&u), Ok("/"));
+
+        let u = Url::parse("mailto:foo@example.com").unwrap();
+        assert_eq!(url_path(&u), Err(UrlError::CannotBeABase));
+    }
+
+    #[test]
+    fn origin() {
+        let robots = Url::parse("https://example.com/robots.txt").unwrap();
+        let origin = robots.origin();
+
+        let u = Url::parse("https://example.com:443/a").unwrap();
+        assert_eq!(url_path_within(&u, Some(&origin)), Ok("/a"));
+
+        let u = Url::parse("http://example.com/a").unwrap();
+        let e = url_path_within(&u, Some(&origin));
+        assert!(matches!(e, Err(UrlError::Origin { .. })));
+        assert_eq!(url_path_within(&u, None), Ok("/a"));
+    }
+}
@@ -1,96 +1,58 @@
-use std::cmp::min;
-use std::io::{BufReader, Error as IoError, Read};
-use std::time::Duration;
+use std::io::{Error as IoError, Read};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
-use url::Url;
+use url::{Origin, Url};
 
-use crate::parse::{into_directives, Directive, Rule, Rules};
+use crate::parse::{strip_params, CleanParam, Host, Rules, Scratch, UrlError, ROBOTS_TXT};
+use crate::parse::{url_path_within, AccessResult, Document, Explanation};
+use crate::parse::{Dialect, ParserOptions, Reason, RequestRate, Rule, VisitTime};
 
-///
-fn parse_user_agent(u: &[u8]) -> Option<String> {
-    let u = String::from_utf8(u.to_vec()).ok()?;
-    let u = u.trim().to_lowercase();
-    Some(u)
-}
-
-///
-fn parse_sitemap(u: &[u8]) -> Option<Url> {
-    let u = String::from_utf8(u.to_vec()).ok()?;
-    let u = Url::parse(u.as_str()).ok()?;
-    Some(u)
-}
-
-///
-fn parse_rule(u: &[u8], allow: bool) -> Option<Rule> {
-    let u = String::from_utf8(u.to_vec()).ok()?;
-    let u = Rule::new(u.as_str(), allow).ok()?;
-    Some(u)
-}
-
-///
-fn parse_crawl_delay(u: &[u8]) -> Option<Duration> {
-    let u = String::from_utf8(u.to_vec()).ok()?;
-    let u = u.parse::<f64>().ok()?;
-    let u = Duration::try_from_secs_f64(u).ok()?;
-    Some(u)
-}
-
-///
-const DEFAULT: &str = "*";
+/// The user-agent that matches all non-assigned crawlers.
+pub(crate) const DEFAULT: &str = "*";
 
 /// Google currently enforces a `robots.txt` file size limit of 500 kibibytes (KiB).
 /// See [How Google interprets Robots.txt](https://t.ly/uWvd).
 pub const BYTES_LIMIT: usize = 512_000;
 
-/// The `AccessResult` enum represents the result of the
-/// `robots.txt` retrieval attempt. See [Robots::from_access].
-#[derive(Debug)]
-pub enum AccessResult<'a> {
-    /// The `robots.txt` file was provided by the server and
-    /// ready to be parsed.
-    Successful(&'a [u8]),
-    /// The `robots.txt` file has not been reached after
-    /// at least five redirect hops. Treated as `Unavailable`.
-    Redirect,
-    /// The valid `robots.txt` file does not exist.
-    /// The `Robots` assumes that there are no restrictions.
-    /// The site is fully allowed.
-    Unavailable,
-    /// The `robots.txt` file could not be served.
-    /// The site is fully disallowed.
-    Unreachable,
-}
-
-///
+/// The `RobotsRules` enum represents either the set of rules
+/// or the global rule.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum RobotsRules {
-    Rules(Rules),
+    Rules(Arc<Rules>),
     Always(bool),
 }
 
 /// The `Robots` struct represents the set of directives related to
 /// the specific `user-agent` in the provided `robots.txt` file.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Robots {
     user_agent: String,
     rules: RobotsRules,
     sitemaps: Vec<Url>,
+    clean_params: Vec<CleanParam>,
+    host: Option<Host>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::parse::serde_origin"))]
+    origin: Option<Origin>,
+    truncated: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    replaced: Option<usize>,
 }
 
 impl Robots {
     /// Finds the longest matching user-agent and
     /// if the parser should check non-assigned rules.
-    fn find_agent(directives: &[Directive], user_agent: &str) -> (String, bool) {
-        // Collects all uas.
-        let uas = directives.iter().filter_map(|ua2| match ua2 {
-            Directive::UserAgent(ua2) => String::from_utf8(ua2.to_vec()).ok(),
-            _ => None,
-        });
-
+    pub(crate) fn find_agent<'a>(
+        user_agents: impl Iterator<Item = &'a str>,
+        user_agent: &str,
+        dialect: Dialect,
+    ) -> (String, bool) {
         // Filters out non-acceptable uas.
-        let ua = user_agent.trim().to_lowercase();
-        let uas = uas.map(|ua2| ua2.trim().to_lowercase());
-        let uas = uas.filter(|ua2| ua.starts_with(ua2.as_str()));
+        let ua = dialect.agent_key(user_agent);
+        let uas = user_agents.map(|ua2| dialect.agent_key(ua2));
+        let uas = uas.filter(|ua2| dialect.matches_agent(&ua, ua2));
 
         // Finds the longest ua in the acceptable pool.
         let uas = uas.max_by(|lhs, rhs| lhs.len().cmp(&rhs.len()));
@@ -101,99 +63,47 @@ impl Robots {
         (uas, default)
     }
 
-    /// Creates a new `Robots` from the directives.
-    fn from_directives(directives: &[Directive], user_agent: &str) -> Self {
-        let (user_agent, mut captures_rules) = Self::find_agent(directives, user_agent);
-        let mut captures_group = false;
-
-        let mut rules = Vec::new();
-        let mut delay = None;
-        let mut sitemaps = Vec::new();
-
-        for directive in directives {
-            match directive {
-                Directive::UserAgent(u) => {
-                    if let Some(u) = parse_user_agent(u) {
-                        if !captures_group || !captures_rules {
-                            captures_rules = u.eq(&user_agent);
-                        }
-                    }
-
-                    captures_group = true;
-                    continue;
-                }
-
-                Directive::Sitemap(u) => {
-                    if let Some(u) = parse_sitemap(u) {
-                        sitemaps.push(u);
-                    }
-
-                    continue;
-                }
-
-                Directive::Unknown(_) => continue,
-                _ => captures_group = false,
-            }
-
-            if !captures_rules {
-                continue;
-            }
-
-            match directive {
-                Directive::Allow(u) | Directive::Disallow(u) => {
-                    let allow = matches!(directive, Directive::Allow(_));
-                    if let Some(u) = parse_rule(u, allow) {
-                        rules.push(u)
-                    }
-                }
-
-                Directive::CrawlDelay(u) => {
-                    if let Some(u) = parse_crawl_delay(u) {
-                        delay = delay.map(|c| min(c, u)).or(Some(u));
-                    }
-                }
-
-                _ => unreachable!(),
-            }
-        }
-
-        let rules = Rules::new(rules, delay);
+    /// Creates a new `Robots` from the parsed `Document`.
+    pub fn from_document(document: &Document, user_agent: &str) -> Self {
+        let (user_agent, rules) = document.merge(user_agent);
         Self {
             user_agent,
             rules: RobotsRules::Rules(rules),
-            sitemaps,
+            sitemaps: document.sitemaps().clone(),
+            clean_params: document.clean_params().to_vec(),
+            host: document.host().cloned(),
+            origin: None,
+            truncated: document.truncated(),
+            replaced: document.replaced(),
         }
     }
 
     /// Creates a new `Robots` from the byte slice.
     pub fn from_slice(robots: &[u8], user_agent: &str) -> Self {
-        // Limits the input to 500 kibibytes.
-        let limit = min(robots.len(), BYTES_LIMIT);
-        let robots = &robots[0..limit];
-
-        // Replaces '\x00' with '\n'.
-        let robots = robots.iter().map(|u| match u {
-            b'\x00' => b'\n',
-            v => *v,
-        });
-
-        let robots: Vec<_> = robots.collect();
-        let robots = robots.as_slice();
+        let document = Document::from_slice(robots);
+        Self::from_document(&document, user_agent)
+    }
 
-        let directives = into_directives(robots);
-        Self::from_directives(directives.as_slice(), user_agent)
+    /// Creates a new `Robots` from the byte slice with the specified options.
+    pub fn from_slice_with(robots: &[u8], user_agent: &str, options: &ParserOptions) -> Self {
+        let document = Document::from_slice_with(robots, options);
+        Self::from_document(&document, user_agent)
     }
 
     /// Creates a new `Robots` from the generic reader.
     pub fn from_reader<R: Read>(reader: R, user_agent: &str) -> Result<Self, IoError> {
-        let reader = reader.take(BYTES_LIMIT as u64);
-        let mut reader = BufReader::new(reader);
-
-        let mut buffer = Vec::new();
-        reader.read_to_end(&mut buffer)?;
+        let document = Document::from_reader(reader)?;
+        Ok(Self::from_document(&document, user_agent))
+    }
 
-        let robots = buffer.as_slice();
-        Ok(Self::from_slice(robots, user_agent))
+    /// Creates a new `Robots` from the generic reader with the specified options.
+    pub fn from_reader_with<R: Read>(
+        reader: R,
+        user_agent: &str,
+        options: &ParserOptions,
+    ) -> Result<Self, IoError> {
+        let document = Document::from_reader_with(reader, options)?;
+        Ok(Self::from_document(&document, user_agent))
     }
 
     /// Creates a new `Robots` from the `AccessResult`.
@@ -212,8 +122,20 @@ impl Robots {
             user_agent: user_agent.trim().to_lowercase(),
             rules: RobotsRules::Always(always),
             sitemaps: vec![],
+            clean_params: vec![],
+            host: None,
+            origin: None,
+            truncated: None,
+            replaced: None,
         }
     }
+
+    /// Binds the `Robots` to the origin of the URL, i.e. the URL the
+    /// `robots.txt` file was fetched from. See [Robots::is_allowed_url].
+    pub fn with_origin(mut self, url: &Url) -> Self {
+        self.origin = Some(url.origin());
+        self
+    }
 }
 
 impl Robots {
@@ -222,13 +144,121 @@ impl Robots {
         &self.user_agent
     }
 
+    /// Returns all rules of the user-agent with their positions
+    /// in the original input, sorted by precedence.
+    pub fn rules(&self) -> &[Rule] {
+        match &self.rules {
+            RobotsRules::Rules(rules) => rules.rules(),
+            RobotsRules::Always(_) => &[],
+        }
+    }
+
     /// Returns true if the path is allowed for the user-agent.
     /// NOTE: Expects relative path.
     pub fn is_allowed(&self, path: &str) -> bool {
-        match &self.rules {
-            RobotsRules::Rules(rules) => rules.is_allowed(path),
-            RobotsRules::Always(always) => *always,
-        }
+        self.explain(path).is_allowed()
+    }
+
+    /// Returns true if the absolute URL is allowed for the user-agent.
+    /// Fails if the URL origin differs from the bound origin (if any).
+    /// See [Robots::with_origin].
+    ///
+    /// ```rust
+    /// use robotxt::Robots;
+    /// use url::Url;
+    ///
+    /// let base = Url::parse("https://example.com/robots.txt").unwrap();
+    /// let txt = b"User-Agent: foobot\nDisallow: /*?q=";
+    /// let r = Robots::from_slice(txt, "foobot").with_origin(&base);
+    ///
+    /// let u = Url::parse("https://example.com/search?q=robots").unwrap();
+    /// assert_eq!(r.is_allowed_url(&u), Ok(false));
+    ///
+    /// let u = Url::parse("https://example.org/search?q=robots").unwrap();
+    /// assert!(r.is_allowed_url(&u).is_err());
+    /// ```
+    pub fn is_allowed_url(&self, url: &Url) -> Result<bool, UrlError> {
+        Ok(self.explain_url(url)?.is_allowed())
+    }
+
+    /// Returns true for every path allowed for the user-agent, in order.
+    /// NOTE: Expects relative paths. Reuses the buffers between the paths,
+    /// which makes it faster than calling [Robots::is_allowed] in a loop.
+    ///
+    /// ```rust
+    /// use robotxt::Robots;
+    ///
+    /// let txt = b"User-Agent: foobot\nDisallow: /example/";
+    /// let r = Robots::from_slice(txt, "foobot");
+    ///
+    /// let paths = ["/example/nope.txt", "/yeah.txt"];
+    /// assert_eq!(r.check_many(paths), vec![false, true]);
+    /// ```
+    pub fn check_many<'a>(&self, paths: impl IntoIterator<Item = &'a str>) -> Vec<bool> {
+        let mut scratch = Scratch::default();
+        let paths = paths.into_iter();
+        let paths = paths.map(|u| self.explain_with(u, &mut scratch).is_allowed());
+        paths.collect()
+    }
+
+    /// Returns true for every absolute URL allowed for the user-agent, in order.
+    /// Fails for the URLs with the origin different from the bound one (if any).
+    /// See [Robots::check_many].
+    pub fn check_many_urls<'a>(
+        &self,
+        urls: impl IntoIterator<Item = &'a Url>,
+    ) -> Vec<Result<bool, UrlError>> {
+        let mut scratch = Scratch::default();
+        let urls = urls.into_iter().map(|u| {
+            let path = url_path_within(u, self.origin.as_ref())?;
+            Ok(self.explain_with(path, &mut scratch).is_allowed())
+        });
+
+        urls.collect()
+    }
+
+    /// Returns the decision for the path with the reason it was made for.
+    /// NOTE: Expects relative path.
+    ///
+    /// ```rust
+    /// use robotxt::{Reason, Robots};
+    ///
+    /// let txt = b"User-Agent: foobot\nDisallow: /example/";
+    /// let r = Robots::from_slice(txt, "foobot");
+    ///
+    /// let e = r.explain("/example/nope.txt");
+    /// assert!(!e.is_allowed());
+    /// assert_eq!(e.rule().unwrap().pattern(), "/example/");
+    /// assert_eq!(e.rule().unwrap().position().unwrap().line(), 2);
+    ///
+    /// let e = r.explain("/robots.txt");
+    /// assert_eq!(e.reason(), Reason::RobotsTxt);
+    /// ```
+    pub fn explain(&self, path: &str) -> Explanation<'_> {
+        self.explain_with(path, &mut Scratch::default())
+    }
+
+    /// Returns the decision for the path reusing the buffers of the previous checks.
+    fn explain_with(&self, path: &str, scratch: &mut Scratch) -> Explanation<'_> {
+        let (allowed, reason) = match &self.rules {
+            RobotsRules::Rules(rules) => match rules.explain_with(path, scratch) {
+                Reason::Matched(rule) => (rule.is_allowed(), Reason::Matched(rule)),
+                reason => (true, reason),
+            },
+            RobotsRules::Always(_) if scratch.normalize(path).eq(ROBOTS_TXT) => {
+                (true, Reason::RobotsTxt)
+            }
+            RobotsRules::Always(always) => (*always, Reason::Always),
+        };
+
+        Explanation::new(allowed, &self.user_agent, reason)
+    }
+
+    /// Returns the decision for the absolute URL with the reason it was made for.
+    /// Fails if the URL origin differs from the bound origin (if any).
+    pub fn explain_url(&self, url: &Url) -> Result<Explanation<'_>, UrlError> {
+        let path = url_path_within(url, self.origin.as_ref())?;
+        Ok(self.explain(path))
     }
 
     /// Returns `Some(_)` if the site is fully allowed or disallowed.
@@ -247,15 +277,124 @@ impl Robots {
         }
     }
 
+    /// Returns the request-rate of the user-agent.
+    pub fn request_rate(&self) -> Option<RequestRate> {
+        match &self.rules {
+            RobotsRules::Rules(rules) => rules.request_rate(),
+            RobotsRules::Always(_) => None,
+        }
+    }
+
+    /// Returns the visit-time windows of the user-agent.
+    pub fn visit_times(&self) -> &[VisitTime] {
+        match &self.rules {
+            RobotsRules::Rules(rules) => rules.visit_times(),
+            RobotsRules::Always(_) => &[],
+        }
+    }
+
+    /// Returns the earliest UTC time the next request may be issued at given
+    /// the current time and the time of the last request (if any).
+    ///
+    /// ```rust
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// use robotxt::Robots;
+    ///
+    /// let txt = b"User-Agent: *\nCrawl-Delay: 5\nRequest-Rate: 1/10s";
+    /// let r = Robots::from_slice(txt, "foobot");
+    ///
+    /// let now = SystemTime::UNIX_EPOCH;
+    /// assert_eq!(r.next_request(now, None), now);
+    ///
+    /// let next = r.next_request(now, Some(now));
+    /// assert_eq!(next, now + Duration::from_secs(10));
+    /// ```
+    pub fn next_request(&self, now: SystemTime, last: Option<SystemTime>) -> SystemTime {
+        match &self.rules {
+            RobotsRules::Rules(rules) => rules.next_request(now, last),
+            RobotsRules::Always(_) => now,
+        }
+    }
+
     /// Returns all sitemaps.
     pub fn sitemaps(&self) -> &Vec<Url> {
         &self.sitemaps
     }
+
+    /// Returns all `clean-param` directives.
+    pub fn clean_params(&self) -> &[CleanParam] {
+        &self.clean_params
+    }
+
+    /// Returns the absolute URL without the query parameters listed by
+    /// the `clean-param` directives matching its path.
+    /// Fails if the URL origin differs from the bound origin (if any).
+    ///
+    /// ```rust
+    /// use robotxt::Robots;
+    /// use url::Url;
+    ///
+    /// let txt = b"User-Agent: *\nClean-Param: ref&sid /forum/";
+    /// let r = Robots::from_slice(txt, "foobot");
+    ///
+    /// let u = Url::parse("https://example.com/forum/?id=1&sid=2").unwrap();
+    /// let u = r.clean_url(&u).unwrap();
+    /// assert_eq!(u.as_str(), "https://example.com/forum/?id=1");
+    /// ```
+    pub fn clean_url(&self, url: &Url) -> Result<Url, UrlError> {
+        url_path_within(url, self.origin.as_ref())?;
+        Ok(strip_params(url, &self.clean_params))
+    }
+
+    /// Returns the preferred mirror (if any).
+    pub fn host(&self) -> Option<&Host> {
+        self.host.as_ref()
+    }
+
+    /// Returns the absolute URL moved onto the preferred mirror (if any).
+    /// Fails if the URL origin differs from the bound origin (if any).
+    ///
+    /// ```rust
+    /// use robotxt::Robots;
+    /// use url::Url;
+    ///
+    /// let txt = b"User-Agent: *\nDisallow: /a\nHost: https://example.com";
+    /// let r = Robots::from_slice(txt, "foobot");
+    ///
+    /// let u = Url::parse("http://www.example.com/b?c").unwrap();
+    /// let u = r.preferred_url(&u).unwrap();
+    /// assert_eq!(u.as_str(), "https://example.com/b?c");
+    /// ```
+    pub fn preferred_url(&self, url: &Url) -> Result<Url, UrlError> {
+        url_path_within(url, self.origin.as_ref())?;
+        match &self.host {
+            Some(host) => Ok(host.rewrite(url)),
+            None => Ok(url.clone()),
+        }
+    }
+
+    /// Returns the byte the oversized input was truncated at (if any).
+    pub fn truncated(&self) -> Option<usize> {
+        self.truncated
+    }
+
+    /// Returns the byte the first malformed sequence
+    /// was replaced at while transcoding (if any).
+    pub fn replaced(&self) -> Option<usize> {
+        self.replaced
+    }
+
+    /// Returns the bound origin (if any).
+    pub fn origin(&self) -> Option<&Origin> {
+        self.origin.as_ref()
+    }
 }
 
 #[cfg(test)]
 mod precedence {
     use super::*;
+    use crate::parse::{Directive, Token};
 
     static DIRECTIVES: &[Directive] = &[
         Directive::UserAgent(b"bot-robotxt"),
@@ -269,9 +408,14 @@ mod precedence {
         Directive::Disallow(b"/"),
     ];
 
+    fn robots(user_agent: &str) -> Robots {
+        let tokens: Vec<_> = DIRECTIVES.iter().map(|d| Token::from(*d)).collect();
+        Document::from_directives(&tokens, Dialect::default()).robots(user_agent)
+    }
+
     #[test]
     fn specific() {
-        let r = Robots::from_directives(DIRECTIVES, "bot-robotxt");
+        let r = robots("bot-robotxt");
 
         // Matches:
         assert!(r.is_allowed("/1"));
@@ -283,7 +427,7 @@ mod precedence {
 
     #[test]
     fn strict() {
-        let r = Robots::from_directives(DIRECTIVES, "bot");
+        let r = robots("bot");
 
         // Matches:
         assert!(r.is_allowed("/3"));
@@ -295,7 +439,7 @@ mod precedence {
 
     #[test]
     fn missing() {
-        let r = Robots::from_directives(DIRECTIVES, "super-bot");
+        let r = robots("super-bot");
 
         // Matches:
         assert!(r.is_allowed("/2"));
@@ -307,7 +451,7 @@ mod precedence {
 
     #[test]
     fn partial() {
-        let r = Robots::from_directives(DIRECTIVES, "bot-super");
+        let r = robots("bot-super");
 
         // Matches:
         assert!(r.is_allowed("/3"));
@@ -317,3 +461,319 @@ mod precedence {
         assert!(!r.is_allowed("/2"));
     }
 }
+
+#[cfg(test)]
+mod positions {
+    use super::*;
+
+    #[test]
+    fn rules() {
+        let txt = b"User-Agent: foobot\nAllow: /a\n\n# comment\nDisallow: /abc\n";
+        let r = Robots::from_slice(txt, "foobot");
+
+        let lines: Vec<_> = r.rules().iter().map(|r| r.position()).collect();
+        let lines: Vec<_> = lines.iter().map(|p| p.map(|p| p.line())).collect();
+        assert_eq!(lines, vec![Some(5), Some(2)]);
+
+        let value = r.rules()[0].position().unwrap().value();
+        assert_eq!(&txt[value.range()], b"/abc");
+    }
+}
+
+#[cfg(test)]
+mod explaining {
+    use super::*;
+
+    #[test]
+    fn always() {
+        let r = Robots::from_access(AccessResult::Unreachable, "foobot");
+        let e = r.explain("/index.html");
+
+        assert!(!e.is_allowed());
+        assert!(e.is_default());
+        assert_eq!(e.reason(), Reason::Always);
+
+        let e = r.explain("/robots.txt");
+        assert!(e.is_allowed());
+        assert_eq!(e.reason(), Reason::RobotsTxt);
+    }
+
+    #[test]
+    fn group() {
+        let txt = b"User-Agent: foo\nDisallow: /a\nUser-Agent: *\nDisallow: /b";
+        let r = Robots::from_slice(txt, "foobot");
+        let e = r.explain("/a/b");
+
+        assert!(!e.is_allowed());
+        assert_eq!(e.user_agent(), "foo");
+        assert_eq!(
+            e.to_string(),
+            "disallowed for user-agent `foo`: matched `Disallow: /a` on line 2"
+        );
+
+        let e = r.explain("/b");
+        assert!(e.is_allowed());
+        assert_eq!(e.reason(), Reason::Unmatched);
+    }
+}
+
+#[cfg(test)]
+mod urls {
+    use super::*;
+
+    fn robots() -> Robots {
+        let base = Url::parse("https://example.com/robots.txt").unwrap();
+        let txt = b"User-Agent: *\nDisallow: /\nAllow: /a?b=c$";
+        Robots::from_slice(txt, "foobot").with_origin(&base)
+    }
+
+    #[test]
+    fn query() {
+        let r = robots();
+
+        let u = Url::parse("https://example.com/a?b=c").unwrap();
+        assert_eq!(r.is_allowed_url(&u), Ok(true));
+
+        let u = Url::parse("https://example.com/a?b=c#d").unwrap();
+        assert_eq!(r.is_allowed_url(&u), Ok(true));
+
+        let u = Url::parse("https://example.com/a?b=cd").unwrap();
+        assert_eq!(r.is_allowed_url(&u), Ok(false));
+    }
+
+    #[test]
+    fn exemption() {
+        let u = Url::parse("https://example.com/robots.txt").unwrap();
+        assert_eq!(robots().is_allowed_url(&u), Ok(true));
+
+        let r = Robots::from_always(false, "foobot");
+        assert_eq!(r.is_allowed_url(&u), Ok(true));
+
+        let u = Url::parse("https://example.com/robots.txt?a").unwrap();
+        assert_eq!(robots().is_allowed_url(&u), Ok(false));
+    }
+
+    #[test]
+    fn batch() {
+        let r = robots();
+        let paths = ["/a?b=c", "/robots.txt", "/a?b=cd", "/"];
+        let expected: Vec<_> = paths.iter().map(|u| r.is_allowed(u)).collect();
+        assert_eq!(r.check_many(paths), expected);
+
+        let u = Url::parse("https://example.com/a?b=c").unwrap();
+        let v = Url::parse("http://example.com/a?b=c").unwrap();
+        let r = r.check_many_urls([&u, &v]);
+        assert_eq!(r[0], Ok(true));
+        assert!(matches!(r[1], Err(UrlError::Origin { .. })));
+    }
+
+    #[test]
+    fn origin() {
+        let u = Url::parse("http://example.com/a?b=c").unwrap();
+        let e = robots().is_allowed_url(&u);
+        assert!(matches!(e, Err(UrlError::Origin { .. })));
+    }
+}
+
+#[cfg(test)]
+mod truncation {
+    use super::*;
+
+    #[test]
+    fn reader() {
+        let mut txt = b"User-Agent: *\nDisallow: /a\n".to_vec();
+        txt.extend(b"#".repeat(BYTES_LIMIT));
+        txt.extend(b"\nDisallow: /b\n");
+
+        let r = Robots::from_reader(txt.as_slice(), "foobot").unwrap();
+        assert_eq!(r.truncated(), Some(BYTES_LIMIT));
+        assert!(!r.is_allowed("/a") && r.is_allowed("/b"));
+
+        let options = ParserOptions::default().limit(txt.len());
+        let r = Robots::from_reader_with(txt.as_slice(), "foobot", &options).unwrap();
+        assert_eq!(r.truncated(), None);
+        assert!(!r.is_allowed("/a") && !r.is_allowed("/b"));
+    }
+}
+
+#[cfg(test)]
+mod encodings {
+    use super::*;
+    use crate::parse::Charset;
+
+    static TXT: &str = "User-Agent: фубот\r\nDisallow: /а\r\nAllow: /а/б";
+
+    fn utf16(txt: &str) -> Vec<u8> {
+        let units = [0xfeff].into_iter().chain(txt.encode_utf16());
+        units.flat_map(|u| u.to_le_bytes()).collect()
+    }
+
+    fn check(txt: &[u8], options: &ParserOptions) {
+        let r = Robots::from_slice_with(txt, "фубот", options);
+        assert!(!r.is_allowed("/а") && r.is_allowed("/а/б"));
+
+        let r = Robots::from_reader_with(txt, "фубот", options).unwrap();
+        assert!(!r.is_allowed("/а") && r.is_allowed("/а/б"));
+    }
+
+    #[test]
+    fn marks() {
+        check(&utf16(TXT), &ParserOptions::default());
+    }
+
+    #[test]
+    fn hints() {
+        let (txt, _, _) = encoding_rs::WINDOWS_1251.encode(TXT);
+        let options = ParserOptions::default();
+        let r = Robots::from_slice_with(&txt, "фубот", &options);
+        assert!(r.is_allowed("/а"));
+
+        let options = options.content_type("text/plain; charset=windows-1251");
+        check(&txt, &options);
+
+        let charset = Charset::for_label("utf-16le").unwrap();
+        let txt = utf16(TXT);
+        check(&txt[2..], &ParserOptions::default().charset(charset));
+    }
+}
+
+#[cfg(test)]
+mod dialects {
+    use super::*;
+
+    static TXT: &[u8] = b"User-Agent: foobot/1.0\n\
+        Crawl-Delay: 5\n\
+        Allow: /a\n\
+        Dissallow: /b\n\
+        User-Agent: foo\n\
+        Disallow: /c\n\
+        User-Agent: *\n\
+        Disallow: /\n";
+
+    fn robots(user_agent: &str, dialect: Dialect) -> Robots {
+        let options = ParserOptions::default().dialect(dialect);
+        Robots::from_slice_with(TXT, user_agent, &options)
+    }
+
+    #[test]
+    fn lenient() {
+        let r = robots("foobot/1.0", Dialect::Lenient);
+        assert_eq!(r.user_agent(), "foobot/1.0");
+        assert_eq!(r.crawl_delay(), Some(Duration::from_secs(5)));
+        assert!(!r.is_allowed("/b"));
+
+        let r = robots("foobot", Dialect::Lenient);
+        assert_eq!(r.user_agent(), "foo");
+    }
+
+    #[test]
+    fn standard() {
+        let r = robots("FooBot", Dialect::Standard);
+        assert_eq!(r.user_agent(), "foobot");
+        assert_eq!(r.crawl_delay(), None);
+        assert!(r.is_allowed("/a") && r.is_allowed("/b"));
+        assert!(r.is_allowed("/c") && r.is_allowed("/d"));
+
+        let r = robots("foo", Dialect::Standard);
+        assert!(!r.is_allowed("/c") && r.is_allowed("/d"));
+    }
+
+    #[test]
+    fn google() {
+        let r = robots("foobot", Dialect::Google);
+        assert_eq!(r.crawl_delay(), None);
+        assert!(r.is_allowed("/a") && !r.is_allowed("/b"));
+
+        let r = robots("foobot-image", Dialect::Google);
+        assert_eq!(r.user_agent(), "*");
+    }
+
+    #[test]
+    fn bing() {
+        let r = robots("foobot", Dialect::Bing);
+        assert_eq!(r.crawl_delay(), Some(Duration::from_secs(5)));
+        assert!(r.is_allowed("/b"));
+    }
+
+    #[test]
+    fn clean_param() {
+        let txt = b"User-Agent: *\nDisallow: /a\nClean-Param: ref\nDisallow: /b";
+        let options = ParserOptions::default().dialect(Dialect::Yandex);
+        let r = Robots::from_slice_with(txt, "foobot", &options);
+        assert_eq!(r.clean_params().len(), 1);
+        assert!(!r.is_allowed("/b"));
+
+        let u = Url::parse("https://example.com/a?ref=1").unwrap();
+        let u = r.clean_url(&u).unwrap();
+        assert_eq!(u.as_str(), "https://example.com/a");
+
+        let options = ParserOptions::default().dialect(Dialect::Google);
+        let r = Robots::from_slice_with(txt, "foobot", &options);
+        assert!(r.clean_params().is_empty());
+    }
+
+    #[test]
+    fn host() {
+        let txt = b"User-Agent: *\nHost: example.com:port\nHost: example.com\nHost: example.org";
+        let options = ParserOptions::default().dialect(Dialect::Yandex);
+        let r = Robots::from_slice_with(txt, "foobot", &options);
+        assert_eq!(
+            r.host().map(|h| h.to_string()),
+            Some("example.com".to_string())
+        );
+
+        let options = ParserOptions::default().dialect(Dialect::Standard);
+        let r = Robots::from_slice_with(txt, "foobot", &options);
+        assert!(r.host().is_none());
+
+        let u = Url::parse("https://example.org/a").unwrap();
+        assert_eq!(r.preferred_url(&u), Ok(u));
+    }
+
+    #[test]
+    fn yandex() {
+        let r = robots("foobot", Dialect::Yandex);
+        assert_eq!(r.user_agent(), "foo");
+        assert_eq!(r.crawl_delay(), None);
+        assert!(!r.is_allowed("/c") && r.is_allowed("/b"));
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod serialization {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let base = Url::parse("https://example.com/robots.txt").unwrap();
+        let txt = b"User-Agent: foobot\nDisallow: /*.php$\nAllow: /a\nCrawl-Delay: 2\n\
+            Request-Rate: 1/5s\nClean-Param: ref\nHost: example.com\n\
+            Sitemap: https://example.com/sitemap.xml";
+        let r = Robots::from_slice(txt, "foobot").with_origin(&base);
+
+        let json = serde_json::to_string(&r).unwrap();
+        let d: Robots = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&d).unwrap(), json);
+
+        for path in ["/a/b.php", "/a", "/b.php5", "/b.php"] {
+            assert_eq!(d.is_allowed(path), r.is_allowed(path), "{path}");
+        }
+
+        assert_eq!(d.rules(), r.rules());
+        assert_eq!(d.rules()[0].position(), r.rules()[0].position());
+        assert_eq!(d.crawl_delay(), r.crawl_delay());
+        assert_eq!(d.request_rate(), r.request_rate());
+        assert_eq!(d.sitemaps(), r.sitemaps());
+        assert_eq!(d.clean_params(), r.clean_params());
+        assert_eq!(d.host(), r.host());
+        assert_eq!(d.origin(), r.origin());
+    }
+
+    #[test]
+    fn always() {
+        let r = Robots::from_always(false, "foobot");
+        let json = serde_json::to_string(&r).unwrap();
+        let d: Robots = serde_json::from_str(&json).unwrap();
+        assert_eq!(d.is_always(), Some(false));
+    }
+}
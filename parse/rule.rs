@@ -1,147 +1,242 @@
 use std::cmp::Ordering;
-use std::error::Error;
-use std::fmt::{Display, Formatter, Result as FmtResult};
 
-use once_cell::sync::OnceCell;
-use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
-use regex::{escape, Error as RegexError, Regex, RegexBuilder};
+use crate::parse::Position;
 
-/// An error type indicating that a `Wildcard` could not be parsed correctly.
-#[derive(Debug, Clone)]
-pub enum WildcardError {
-    // EndingTooMany(usize),
-    // EndingPosition(usize),
-    Regex(RegexError),
-}
-
-impl Display for WildcardError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        match &self {
-            Self::Regex(e) => Display::fmt(e, f),
-        }
-    }
-}
-
-impl From<RegexError> for WildcardError {
-    fn from(error: RegexError) -> Self {
-        Self::Regex(error)
-    }
-}
-
-impl Error for WildcardError {}
-
-/// The `Wildcard` struct provides efficient pattern matching for wildcards.
+/// The `Wildcard` enum provides linear-time pattern matching for wildcards.
+/// NOTE: The patterns are expected to have consecutive `*` collapsed.
 #[derive(Debug, Clone)]
 pub enum Wildcard {
-    // Ending(String),
+    /// The pattern ends with `$` and has no `*`, i.e. the exact match.
+    Ending(String),
+    /// The pattern has `*` but does not end with `$`.
     Universal(String),
-    Both(Regex),
+    /// The pattern has `*` and ends with `$`.
+    Both(String),
+    /// The pattern has `$` followed by other characters.
+    Never,
 }
 
 impl Wildcard {
     /// Creates a new `Wildcard` with the specified pattern or returns
     /// `None` if the specified pattern does not contain any wildcard.
-    pub fn new(pattern: &str) -> Result<Option<Self>, WildcardError> {
+    pub fn new(pattern: &str) -> Option<Self> {
         if !pattern.contains('$') && !pattern.contains('*') {
-            return Ok(None);
+            return None;
         }
 
-        // TODO: Only end of pattern wildcard.
-        // if pattern.contains('$') && !pattern.contains('*') { }
+        // Collapses consecutive '*' as they are equivalent to a single one.
+        let mut collapsed = String::with_capacity(pattern.len());
+        for c in pattern.chars() {
+            if c != '*' || !collapsed.ends_with('*') {
+                collapsed.push(c);
+            }
+        }
 
-        static STAR_KILLER: OnceCell<Regex> = OnceCell::new();
-        let star_killer = STAR_KILLER.get_or_init(|| Regex::new(r"\*+").unwrap());
-        let pattern = star_killer.replace_all(pattern, "*");
+        // Only '*' and '$' may follow the first '$' for the pattern to ever match,
+        // i.e. '/a$*' is equivalent to '/a$' and '/a$b' never matches.
+        let (body, anchored) = match collapsed.find('$') {
+            None => (collapsed.as_str(), false),
+            Some(i) if collapsed[i..].chars().all(|c| c == '$' || c == '*') => {
+                (&collapsed[..i], true)
+            }
+            Some(_) => return Some(Self::Never),
+        };
+
+        let body = body.to_string();
+        Some(match (anchored, body.contains('*')) {
+            (false, _) => Self::Universal(body),
+            (true, false) => Self::Ending(body),
+            (true, true) => Self::Both(body),
+        })
+    }
 
-        // TODO: Optimize wildcard checks.
-        if pattern.contains('*') && !pattern.contains('$') {
-            return Ok(Some(Self::Universal(pattern.to_string())));
+    /// Returns the position after the longest prefix of the path that
+    /// matches the pattern with the leftmost segment occurrences.
+    /// NOTE: Runs in linear time as every segment is searched only once.
+    fn match_segments<'a>(
+        mut segments: impl Iterator<Item = &'a str>,
+        path: &str,
+    ) -> Option<usize> {
+        // The first segment is special as it doesn't start with a '*'.
+        // i.e. pattern '/a*c' : path '/abc' should match '/a'.
+        let first = segments.next().unwrap_or_default();
+        if !path.starts_with(first) {
+            return None;
         }
 
-        let regex = escape(&pattern).replace("\\*", ".*").replace("\\$", "$");
-        let regex = '^'.to_string() + &regex;
-
-        let regex = RegexBuilder::new(&regex)
-            .dfa_size_limit(42 * (1 << 10))
-            .size_limit(42 * (1 << 10))
-            .build()?;
+        let mut pos = first.len();
+        for segment in segments {
+            pos += path[pos..].find(segment)? + segment.len();
+        }
 
-        Ok(Some(Self::Both(regex)))
+        Some(pos)
     }
 
-    /// Returns true if the path matches the pattern.
-    /// TODO Clean up the mess.
+    /// Returns true if the path matches the unanchored pattern.
     fn match_universal(pattern: &str, path: &str) -> bool {
-        let splits = pattern.split('*');
-
-        let mut pos = 0;
-        for (idx, split) in splits.enumerate() {
-            // The first split is special as it doesn't start with a '*'.
-            // i.e. pattern '/a*c' : path '/abc' should match '/a'.
-            if idx == 0 && !path.is_empty() {
-                if !path.starts_with(split) {
-                    return false;
-                }
+        Self::match_segments(pattern.split('*'), path).is_some()
+    }
 
-                pos += split.len();
-                continue;
-            }
+    /// Returns true if the path matches the pattern anchored at the end.
+    fn match_both(pattern: &str, path: &str) -> bool {
+        // The last segment must be the suffix of the path.
+        let (body, last) = match pattern.rsplit_once('*') {
+            Some(split) => split,
+            None => return path.eq(pattern),
+        };
 
-            match path[pos..].find(split) {
-                Some(idx) => {
-                    pos += idx + split.len();
-                }
-                None => return false,
-            }
-        }
+        let pos = match Self::match_segments(body.split('*'), path) {
+            Some(pos) => pos,
+            None => return false,
+        };
 
-        true
+        path.len() >= pos + last.len() && path.ends_with(last)
     }
 
     /// Returns true if the path matches the wildcard pattern.
     pub fn is_match(&self, path: &str) -> bool {
         match &self {
-            // Self::Ending(_) => todo!(), // NOTE: Only end of pattern wildcard.
+            Self::Ending(p) => path.eq(p),
             Self::Universal(p) => Self::match_universal(p.as_str(), path),
-            Self::Both(r) => r.is_match(path),
+            Self::Both(p) => Self::match_both(p.as_str(), path),
+            Self::Never => false,
+        }
+    }
+}
+
+/// Returns true if the normalized relative path matches the normalized
+/// pattern without compiling it. See [Wildcard::new].
+pub(crate) fn match_pattern(pattern: &str, path: &str) -> bool {
+    match pattern.find('$') {
+        None => Wildcard::match_universal(pattern, path),
+        Some(i) if pattern[i..].chars().all(|c| c == '$' || c == '*') => {
+            Wildcard::match_both(&pattern[..i], path)
         }
+        Some(_) => false,
     }
 }
 
-/// Returns the prefixed & percent-encoded path.
+/// Returns the prefixed & percent-encoded path normalized as required by
+/// [Request for Comments: 9309](https://www.rfc-editor.org/rfc/rfc9309.html#section-2.2.2):
+///
+/// - non-ASCII, control and unsafe characters are percent-encoded.
+/// - percent-encoded unreserved characters are decoded.
+/// - hex digits of the remaining percent-encoded octets are uppercase.
+///
 /// NOTE: Expects relative path.
 pub fn normalize_path(path: &str) -> String {
-    // TODO: Replace once_cell with std::sync::OnceLock once stable.
-    static FRAGMENT: OnceCell<AsciiSet> = OnceCell::new();
-    let fragment = FRAGMENT.get_or_init(|| CONTROLS.add(b' ').add(b'"').add(b'<').add(b'>'));
-    let path = utf8_percent_encode(path, fragment).to_string();
+    let mut buffer = String::with_capacity(path.len() + 1);
+    normalize_path_into(path, &mut buffer);
+    buffer
+}
+
+/// Writes the prefixed & normalized path into the cleared buffer.
+/// See [normalize_path].
+pub(crate) fn normalize_path_into(path: &str, buffer: &mut String) {
+    const HEX: &[u8; 16] = b"0123456789ABCDEF";
+    let encode = |buffer: &mut String, u: u8| {
+        buffer.push('%');
+        buffer.push(HEX[(u >> 4) as usize] as char);
+        buffer.push(HEX[(u & 0xF) as usize] as char);
+    };
+
+    buffer.clear();
+    if !path.starts_with('/') {
+        buffer.push('/');
+    }
+
+    let bytes = path.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let u = bytes[i];
+        i += 1;
+
+        match u {
+            b'%' => {
+                let hex = bytes
+                    .get(i..i + 2)
+                    .filter(|u| u.iter().all(u8::is_ascii_hexdigit));
+                let hex = hex.and_then(|u| std::str::from_utf8(u).ok());
+                match hex.and_then(|u| u8::from_str_radix(u, 16).ok()) {
+                    Some(u) if is_unreserved(u) => buffer.push(u as char),
+                    Some(u) => encode(buffer, u),
+                    // The stray '%' is encoded itself.
+                    None => {
+                        encode(buffer, b'%');
+                        continue;
+                    }
+                }
+
+                i += 2;
+            }
 
-    match path.starts_with('/') {
-        false => '/'.to_string() + path.as_str(),
-        true => path,
+            u if u.is_ascii_control() || !u.is_ascii() => encode(buffer, u),
+            b' ' | b'"' | b'<' | b'>' => encode(buffer, u),
+            u => buffer.push(u as char),
+        }
     }
 }
 
+/// Returns true if the path is already normalized, i.e. [normalize_path]
+/// would return it unchanged. See [normalize_path].
+pub(crate) fn is_normalized(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    let upper = |u: &u8| u.is_ascii_digit() || (b'A'..=b'F').contains(u);
+    if !path.starts_with('/') {
+        return false;
+    }
+
+    bytes.iter().enumerate().all(|(i, u)| match u {
+        b'%' => match bytes.get(i + 1..i + 3) {
+            Some([h, l]) if upper(h) && upper(l) => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                let hex = hex.and_then(|u| u8::from_str_radix(u, 16).ok());
+                hex.is_some_and(|u| !is_unreserved(u))
+            }
+            _ => false,
+        },
+        u if u.is_ascii_control() || !u.is_ascii() => false,
+        b' ' | b'"' | b'<' | b'>' => false,
+        _ => true,
+    })
+}
+
+/// Returns true if the character is unreserved, i.e. never has to be encoded.
+/// See [Request for Comments: 3986](https://www.rfc-editor.org/rfc/rfc3986.html#section-2.3).
+fn is_unreserved(u: u8) -> bool {
+    u.is_ascii_alphanumeric() || matches!(u, b'-' | b'.' | b'_' | b'~')
+}
+
 /// The `Rule` struct provides a convenient and efficient way to process
 /// and to match robots.txt provided patterns with relative paths.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "RuleData", into = "RuleData"))]
 pub struct Rule {
     pattern: String,
     allow: bool,
     wildcard: Option<Wildcard>,
+    position: Option<Position>,
 }
 
 impl Rule {
     /// Creates a new `Rule` with the specified pattern and permission.
-    pub fn new(pattern: &str, allow: bool) -> Result<Self, WildcardError> {
+    pub fn new(pattern: &str, allow: bool) -> Self {
         let pattern = normalize_path(pattern);
-        let wildcard = Wildcard::new(pattern.as_str())?;
+        let wildcard = Wildcard::new(pattern.as_str());
 
-        Ok(Self {
+        Self {
             pattern,
             allow,
             wildcard,
-        })
+            position: None,
+        }
+    }
+
+    /// Attaches the position of the directive in the original input.
+    pub fn with_position(mut self, position: Position) -> Self {
+        self.position = Some(position);
+        self
     }
 
     /// Returns true if the normalized relative path matches the pattern.
@@ -153,10 +248,81 @@ impl Rule {
         }
     }
 
+    /// Returns true if the pattern has no wildcards.
+    pub(crate) fn is_plain(&self) -> bool {
+        self.wildcard.is_none()
+    }
+
+    /// Returns true if the pattern can never match.
+    pub(crate) fn is_unmatchable(&self) -> bool {
+        matches!(self.wildcard, Some(Wildcard::Never))
+    }
+
+    /// Returns the part of the pattern preceding the first wildcard.
+    pub(crate) fn literal_prefix(&self) -> &str {
+        let end = self.pattern.find(['*', '$']);
+        &self.pattern[..end.unwrap_or(self.pattern.len())]
+    }
+
+    /// Returns the distinct parts of the pattern between the wildcards
+    /// following the literal prefix, i.e. every one must occur in the path.
+    pub(crate) fn literal_segments(&self) -> Vec<&str> {
+        let end = self.pattern.find('$').unwrap_or(self.pattern.len());
+        let segments = self.pattern[..end].split('*').skip(1);
+        let mut segments: Vec<_> = segments.filter(|u| !u.is_empty()).collect();
+        segments.sort_unstable();
+        segments.dedup();
+        segments
+    }
+
     /// Returns true if allowed.
     pub fn is_allowed(&self) -> bool {
         self.allow
     }
+
+    /// Returns the normalized pattern.
+    pub fn pattern(&self) -> &str {
+        self.pattern.as_str()
+    }
+
+    /// Returns the position of the directive in the original input.
+    /// NOTE: Only available for the parsed rules.
+    pub fn position(&self) -> Option<Position> {
+        self.position
+    }
+}
+
+/// The `RuleData` struct represents the serialized `Rule`
+/// without the compiled wildcard.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct RuleData {
+    pattern: String,
+    allow: bool,
+    position: Option<Position>,
+}
+
+#[cfg(feature = "serde")]
+impl From<RuleData> for Rule {
+    fn from(data: RuleData) -> Self {
+        // Normalization is idempotent, i.e. the pattern is kept as is.
+        let rule = Self::new(data.pattern.as_str(), data.allow);
+        Self {
+            position: data.position,
+            ..rule
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Rule> for RuleData {
+    fn from(rule: Rule) -> Self {
+        Self {
+            pattern: rule.pattern,
+            allow: rule.allow,
+            position: rule.position,
+        }
+    }
 }
 
 impl PartialEq<Self> for Rule {
@@ -188,7 +354,7 @@ mod matching {
 
     #[test]
     fn root_none() {
-        let r = Rule::new("/", true).unwrap();
+        let r = Rule::new("/", true);
 
         // Matches:
         assert!(r.is_match("/fish"));
@@ -196,7 +362,7 @@ mod matching {
 
     #[test]
     fn root_universal() {
-        let r = Rule::new("/*", true).unwrap();
+        let r = Rule::new("/*", true);
 
         // Matches:
         assert!(r.is_match("/fish"));
@@ -204,7 +370,7 @@ mod matching {
 
     #[test]
     fn root_ending() {
-        let r = Rule::new("/$", true).unwrap();
+        let r = Rule::new("/$", true);
 
         // Matches:
         assert!(r.is_match("/"));
@@ -215,7 +381,7 @@ mod matching {
 
     #[test]
     fn simple() {
-        let r = Rule::new("/fish", true).unwrap();
+        let r = Rule::new("/fish", true);
 
         // Matches:
         assert!(r.is_match("/fish"));
@@ -234,7 +400,7 @@ mod matching {
 
     #[test]
     fn folder() {
-        let r = Rule::new("/fish/", true).unwrap();
+        let r = Rule::new("/fish/", true);
 
         // Matches:
         assert!(r.is_match("/fish/"));
@@ -250,7 +416,7 @@ mod matching {
 
     #[test]
     fn universal_end() {
-        let r = Rule::new("/fish*", true).unwrap();
+        let r = Rule::new("/fish*", true);
 
         // Matches:
         assert!(r.is_match("/fish"));
@@ -269,7 +435,7 @@ mod matching {
 
     #[test]
     fn universal_mid() {
-        let r = Rule::new("/*.php", true).unwrap();
+        let r = Rule::new("/*.php", true);
 
         // Matches:
         assert!(r.is_match("/index.php"));
@@ -286,7 +452,7 @@ mod matching {
 
     #[test]
     fn universal_mid2() {
-        let r = Rule::new("/fish*.php", true).unwrap();
+        let r = Rule::new("/fish*.php", true);
 
         // Matches:
         assert!(r.is_match("/fish.php"));
@@ -298,7 +464,7 @@ mod matching {
 
     #[test]
     fn both_wildcards() {
-        let r = Rule::new("/*.php$", true).unwrap();
+        let r = Rule::new("/*.php$", true);
 
         // Matches:
         assert!(r.is_match("/filename.php"));
@@ -310,4 +476,123 @@ mod matching {
         assert!(!r.is_match("/filename.php5"));
         assert!(!r.is_match("/windows.PHP"));
     }
+
+    #[test]
+    fn ending() {
+        let r = Rule::new("/fish$", true);
+
+        // Matches:
+        assert!(r.is_match("/fish"));
+
+        // Doesn't match:
+        assert!(!r.is_match("/fish/"));
+        assert!(!r.is_match("/fishheads"));
+    }
+
+    #[test]
+    fn misplaced_ending() {
+        let r = Rule::new("/fish$*", true);
+        assert!(r.is_match("/fish"));
+        assert!(!r.is_match("/fishheads"));
+
+        let r = Rule::new("/fish$.php", true);
+        assert!(!r.is_match("/fish"));
+        assert!(!r.is_match("/fish$.php"));
+        assert!(!r.is_match("/fish.php"));
+    }
+
+    #[test]
+    fn overlapping() {
+        let r = Rule::new("/*ab*ba$", true);
+
+        // Matches:
+        assert!(r.is_match("/abba"));
+        assert!(r.is_match("/xabxba"));
+
+        // Doesn't match:
+        assert!(!r.is_match("/aba"));
+        assert!(!r.is_match("/abab"));
+    }
+
+    #[test]
+    fn pathological() {
+        let pattern = "/".to_string() + &"*a".repeat(1000) + "*b$";
+        let r = Rule::new(pattern.as_str(), true);
+
+        let path = "/".to_string() + &"a".repeat(100_000);
+        assert!(!r.is_match(path.as_str()));
+        assert!(r.is_match((path + "b").as_str()));
+    }
+}
+
+#[cfg(test)]
+mod normalization {
+    use super::*;
+
+    #[test]
+    fn encoding() {
+        assert_eq!(normalize_path("foo bar"), "/foo%20bar");
+        assert_eq!(normalize_path("/foo/bar/ツ"), "/foo/bar/%E3%83%84");
+        assert_eq!(normalize_path("/a\x00<b>"), "/a%00%3Cb%3E");
+        assert_eq!(normalize_path("/*.php$"), "/*.php$");
+    }
+
+    #[test]
+    fn decoding() {
+        assert_eq!(normalize_path("/%41%7e%2d"), "/A~-");
+        assert_eq!(normalize_path("/%e3%83%84"), "/%E3%83%84");
+        assert_eq!(normalize_path("/a%2fb%2A%24"), "/a%2Fb%2A%24");
+        assert_eq!(normalize_path("/100%"), "/100%25");
+        assert_eq!(normalize_path("/%zz%4"), "/%25zz%254");
+        assert_eq!(normalize_path("/%+1"), "/%25+1");
+    }
+
+    #[test]
+    fn normalized() {
+        let paths = [
+            "",
+            "/",
+            "a",
+            "/a b",
+            "/ツ",
+            "/%E3%83%84",
+            "/%e3",
+            "/%41",
+            "/%2F",
+            "/%2f",
+            "/100%",
+            "/%25",
+            "/%zz",
+            "/*.php$",
+            "/a\x7f",
+            "/a\"b",
+        ];
+
+        for path in paths {
+            let expected = normalize_path(path) == path;
+            assert_eq!(is_normalized(path), expected, "{path}");
+        }
+    }
+
+    /// See [Request for Comments: 9309](https://www.rfc-editor.org/rfc/rfc9309.html#section-2.2.2).
+    #[test]
+    fn conformance() {
+        let cases = [
+            ("/foo/bar?baz=quz", "/foo/bar?baz=quz", true),
+            ("/foo/bar/ツ", "/foo/bar/%E3%83%84", true),
+            ("/foo/bar/%E3%83%84", "/foo/bar/%E3%83%84", true),
+            ("/foo/bar/%E3%83%84", "/foo/bar/ツ", true),
+            ("/foo/bar/%62%61%7A", "/foo/bar/baz", true),
+            ("/foo/%e2%98%83", "/foo/☃", true),
+            ("/a%2Fb", "/a/b", false),
+            ("/a/b", "/a%2Fb", false),
+            ("/%2A", "/a", false),
+        ];
+
+        for (pattern, path, expected) in cases {
+            let r = Rule::new(pattern, false);
+            let path = normalize_path(path);
+            assert_eq!(r.is_match(&path), expected, "{pattern} {path}");
+        }
+    }
 }
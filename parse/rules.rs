@@ -1,13 +1,70 @@
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
-use crate::parse::{normalize_path, Rule};
+use crate::parse::{
+    next_request, normalize_path_into, Lookup, Reason, RequestRate, Rule, RuleIndex, VisitTime,
+};
+
+/// The path of the `robots.txt` file that is always allowed.
+pub(crate) const ROBOTS_TXT: &str = "/robots.txt";
+
+/// The `Scratch` struct holds the buffers reused between the checks.
+#[derive(Debug, Default)]
+pub(crate) struct Scratch {
+    path: String,
+    lookup: Lookup,
+}
+
+impl Scratch {
+    /// Returns the path normalized into the reused buffer.
+    pub fn normalize(&mut self, path: &str) -> &str {
+        normalize_path_into(path, &mut self.path);
+        self.path.as_str()
+    }
+}
 
 /// The `Rules` struct provides a convenient and efficient storage for
 /// the data associated with certain user-agent for further matching.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "RulesData", into = "RulesData"))]
 pub struct Rules {
     rules: Vec<Rule>,
+    index: RuleIndex,
     delay: Option<Duration>,
+    rate: Option<RequestRate>,
+    visit_times: Vec<VisitTime>,
+}
+
+/// The `RulesData` struct represents the serialized `Rules`
+/// without the compiled index.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct RulesData {
+    rules: Vec<Rule>,
+    delay: Option<Duration>,
+    rate: Option<RequestRate>,
+    visit_times: Vec<VisitTime>,
+}
+
+#[cfg(feature = "serde")]
+impl From<RulesData> for Rules {
+    fn from(data: RulesData) -> Self {
+        let rules = Self::new(data.rules, data.delay);
+        let rules = rules.with_request_rate(data.rate);
+        rules.with_visit_times(data.visit_times)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Rules> for RulesData {
+    fn from(rules: Rules) -> Self {
+        Self {
+            rules: rules.rules,
+            delay: rules.delay,
+            rate: rules.rate,
+            visit_times: rules.visit_times,
+        }
+    }
 }
 
 impl Rules {
@@ -18,31 +75,88 @@ impl Rules {
         // Rules are sorted by length and permission i.e.
         // 5a > 4a, 5a > 5d, 5d > 4a
         rules.sort();
-        Self { rules, delay }
+        let index = RuleIndex::new(&rules);
+        Self {
+            rules,
+            index,
+            delay,
+            rate: None,
+            visit_times: Vec::new(),
+        }
+    }
+
+    /// Sets the request-rate.
+    pub fn with_request_rate(mut self, rate: Option<RequestRate>) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    /// Sets the visit-time windows.
+    pub fn with_visit_times(mut self, visit_times: Vec<VisitTime>) -> Self {
+        self.visit_times = visit_times;
+        self
     }
 
     /// Returns true if the relative path is allowed for this set of rules.
     /// NOTE: Expects relative path.
     pub fn is_allowed(&self, path: &str) -> bool {
-        let path = normalize_path(path);
+        match self.explain(path) {
+            Reason::Matched(rule) => rule.is_allowed(),
+            _ => true,
+        }
+    }
 
-        if path.eq("/robots.txt") {
-            return true;
+    /// Returns the reason the relative path is allowed or disallowed.
+    /// NOTE: Expects relative path.
+    pub fn explain(&self, path: &str) -> Reason<'_> {
+        self.explain_with(path, &mut Scratch::default())
+    }
+
+    /// Returns the reason the relative path is allowed or disallowed
+    /// reusing the buffers of the previous checks.
+    /// NOTE: Expects relative path.
+    pub(crate) fn explain_with(&self, path: &str, scratch: &mut Scratch) -> Reason<'_> {
+        normalize_path_into(path, &mut scratch.path);
+        let path = scratch.path.as_str();
+
+        if path.eq(ROBOTS_TXT) {
+            return Reason::RobotsTxt;
         }
 
-        for rule in &self.rules {
-            if rule.is_match(path.as_str()) {
-                return rule.is_allowed();
-            }
+        let lookup = &mut scratch.lookup;
+        match self.index.find_with(&self.rules, path, lookup) {
+            Some(i) => Reason::Matched(&self.rules[i]),
+            None => Reason::Unmatched,
         }
+    }
 
-        true
+    /// Returns all rules sorted by precedence.
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
     }
 
     /// Returns the specified crawl-delay.
     pub fn crawl_delay(&self) -> Option<Duration> {
         self.delay
     }
+
+    /// Returns the specified request-rate.
+    pub fn request_rate(&self) -> Option<RequestRate> {
+        self.rate
+    }
+
+    /// Returns the specified visit-time windows.
+    pub fn visit_times(&self) -> &[VisitTime] {
+        &self.visit_times
+    }
+
+    /// Returns the earliest UTC time the next request may be issued at given
+    /// the current time and the time of the last request (if any).
+    /// NOTE: Combines crawl-delay, request-rate and visit-time windows.
+    pub fn next_request(&self, now: SystemTime, last: Option<SystemTime>) -> SystemTime {
+        let visit_times = self.visit_times.as_slice();
+        next_request(now, last, self.delay, self.rate, visit_times)
+    }
 }
 
 #[cfg(test)]
@@ -51,8 +165,8 @@ mod precedence {
 
     #[test]
     fn simple() {
-        let allow = Rule::new("/p", true).unwrap();
-        let disallow = Rule::new("/", false).unwrap();
+        let allow = Rule::new("/p", true);
+        let disallow = Rule::new("/", false);
         let rules = Rules::new(vec![allow, disallow], None);
 
         assert!(rules.is_allowed("/page"));
@@ -60,8 +174,8 @@ mod precedence {
 
     #[test]
     fn restrictive() {
-        let allow = Rule::new("/folder", true).unwrap();
-        let disallow = Rule::new("/folder", false).unwrap();
+        let allow = Rule::new("/folder", true);
+        let disallow = Rule::new("/folder", false);
         let rules = Rules::new(vec![allow, disallow], None);
 
         assert!(rules.is_allowed("/folder/page"));
@@ -69,8 +183,8 @@ mod precedence {
 
     #[test]
     fn restrictive2() {
-        let allow = Rule::new("/page", true).unwrap();
-        let disallow = Rule::new("/*.ph", false).unwrap();
+        let allow = Rule::new("/page", true);
+        let disallow = Rule::new("/*.ph", false);
         let rules = Rules::new(vec![allow, disallow], None);
 
         assert!(rules.is_allowed("/page.php5"));
@@ -78,8 +192,8 @@ mod precedence {
 
     #[test]
     fn longer() {
-        let allow = Rule::new("/page", true).unwrap();
-        let disallow = Rule::new("/*.htm", false).unwrap();
+        let allow = Rule::new("/page", true);
+        let disallow = Rule::new("/*.htm", false);
         let rules = Rules::new(vec![allow, disallow], None);
 
         assert!(!rules.is_allowed("/page.htm"));
@@ -87,8 +201,8 @@ mod precedence {
 
     #[test]
     fn specific() {
-        let allow = Rule::new("/$", true).unwrap();
-        let disallow = Rule::new("/", false).unwrap();
+        let allow = Rule::new("/$", true);
+        let disallow = Rule::new("/", false);
         let rules = Rules::new(vec![allow, disallow], None);
 
         assert!(rules.is_allowed("/"));
@@ -96,10 +210,34 @@ mod precedence {
 
     #[test]
     fn specific2() {
-        let allow = Rule::new("/$", true).unwrap();
-        let disallow = Rule::new("/", false).unwrap();
+        let allow = Rule::new("/$", true);
+        let disallow = Rule::new("/", false);
         let rules = Rules::new(vec![allow, disallow], None);
 
         assert!(!rules.is_allowed("/page.htm"));
     }
 }
+
+#[cfg(test)]
+mod explaining {
+    use super::*;
+
+    #[test]
+    fn matched() {
+        let allow = Rule::new("/page", true);
+        let disallow = Rule::new("/", false);
+        let rules = Rules::new(vec![allow.clone(), disallow.clone()], None);
+
+        assert_eq!(rules.explain("/page.htm"), Reason::Matched(&allow));
+        assert_eq!(rules.explain("/index.htm"), Reason::Matched(&disallow));
+    }
+
+    #[test]
+    fn defaults() {
+        let disallow = Rule::new("/page", false);
+        let rules = Rules::new(vec![disallow], None);
+
+        assert_eq!(rules.explain("/index.htm"), Reason::Unmatched);
+        assert_eq!(rules.explain("/robots.txt"), Reason::RobotsTxt);
+    }
+}
@@ -0,0 +1,390 @@
+use std::cmp::max;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The number of seconds in the day.
+const DAY: u64 = 24 * 60 * 60;
+
+/// The `RequestRate` struct represents the `request-rate` directive,
+/// i.e. the maximum number of requests per the period of time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(try_from = "RequestRateData", into = "RequestRateData")
+)]
+pub struct RequestRate {
+    requests: u32,
+    period: Duration,
+}
+
+/// The `RequestRateData` struct represents the serialized `RequestRate`
+/// validated on deserialization.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct RequestRateData {
+    requests: u32,
+    period: Duration,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RequestRateData> for RequestRate {
+    type Error = &'static str;
+
+    fn try_from(data: RequestRateData) -> Result<Self, Self::Error> {
+        Self::new(data.requests, data.period).ok_or("request-rate is zero")
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<RequestRate> for RequestRateData {
+    fn from(rate: RequestRate) -> Self {
+        Self {
+            requests: rate.requests,
+            period: rate.period,
+        }
+    }
+}
+
+impl RequestRate {
+    /// Creates a new `RequestRate` with the specified number of requests
+    /// per period or returns `None` if either of them is zero.
+    pub fn new(requests: u32, period: Duration) -> Option<Self> {
+        match requests == 0 || period.is_zero() {
+            true => None,
+            false => Some(Self { requests, period }),
+        }
+    }
+
+    /// Returns the number of requests.
+    pub fn requests(&self) -> u32 {
+        self.requests
+    }
+
+    /// Returns the period of time.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Returns the minimum interval between two consecutive requests.
+    pub fn interval(&self) -> Duration {
+        self.period / self.requests
+    }
+}
+
+/// The `VisitTime` struct represents the `visit-time` directive,
+/// i.e. the daily UTC window the crawler is welcome in.
+/// NOTE: The window wraps around midnight if it ends before it starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(try_from = "VisitTimeData", into = "VisitTimeData")
+)]
+pub struct VisitTime {
+    start: Duration,
+    end: Duration,
+}
+
+/// The `VisitTimeData` struct represents the serialized `VisitTime`
+/// validated on deserialization.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct VisitTimeData {
+    start: Duration,
+    end: Duration,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<VisitTimeData> for VisitTime {
+    type Error = &'static str;
+
+    fn try_from(data: VisitTimeData) -> Result<Self, Self::Error> {
+        let time = Self::from_window(data.start, data.end);
+        time.ok_or("visit-time is out of range or empty")
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<VisitTime> for VisitTimeData {
+    fn from(time: VisitTime) -> Self {
+        Self {
+            start: time.start,
+            end: time.end,
+        }
+    }
+}
+
+impl VisitTime {
+    /// Creates a new `VisitTime` with the specified UTC hours and minutes
+    /// or returns `None` if either of them is out of range or the window is empty.
+    pub fn new(start: (u8, u8), end: (u8, u8)) -> Option<Self> {
+        let time = |(h, m): (u8, u8)| match h < 24 && m < 60 {
+            true => Some(Duration::from_secs(h as u64 * 3600 + m as u64 * 60)),
+            false => None,
+        };
+
+        Self::from_window(time(start)?, time(end)?)
+    }
+
+    /// Creates a new `VisitTime` with the specified window since midnight
+    /// or returns `None` if either bound is out of range or the window is empty.
+    pub(crate) fn from_window(start: Duration, end: Duration) -> Option<Self> {
+        let day = Duration::from_secs(DAY);
+        match start < day && end < day && start != end {
+            true => Some(Self { start, end }),
+            false => None,
+        }
+    }
+
+    /// Returns the start of the window since midnight.
+    pub fn start(&self) -> Duration {
+        self.start
+    }
+
+    /// Returns the end of the window since midnight.
+    pub fn end(&self) -> Duration {
+        self.end
+    }
+
+    /// Returns true if the time is within the window.
+    pub fn contains(&self, time: SystemTime) -> bool {
+        let time = time_of_day(time);
+        match self.start < self.end {
+            true => self.start <= time && time < self.end,
+            false => self.start <= time || time < self.end,
+        }
+    }
+
+    /// Returns the time left until the window opens.
+    /// NOTE: Zero if the time is within the window.
+    pub fn wait(&self, time: SystemTime) -> Duration {
+        if self.contains(time) {
+            return Duration::ZERO;
+        }
+
+        let day = Duration::from_secs(DAY);
+        let time = time_of_day(time);
+        match self.start > time {
+            true => self.start - time,
+            false => day - time + self.start,
+        }
+    }
+}
+
+/// Returns the time elapsed since the UTC midnight.
+fn time_of_day(time: SystemTime) -> Duration {
+    let time = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let days = Duration::from_secs(time.as_secs() / DAY * DAY);
+    time - days
+}
+
+/// Parses the `request-rate` value, i.e. `n/m[s|m|h|d]`, into the `RequestRate`.
+pub(crate) fn parse_request_rate(u: &[u8]) -> Option<RequestRate> {
+    let u = std::str::from_utf8(u).ok()?;
+    let (requests, period) = u.trim().split_once('/')?;
+    let requests = requests.trim().parse::<u32>().ok()?;
+
+    let period = period.trim();
+    let (period, unit) = match period.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => period.split_at(i),
+        None => (period, "s"),
+    };
+
+    let unit = match unit.trim().as_bytes() {
+        b"s" | b"S" => 1,
+        b"m" | b"M" => 60,
+        b"h" | b"H" => 60 * 60,
+        b"d" | b"D" => DAY,
+        _ => return None,
+    };
+
+    let period = period.parse::<u64>().ok()?.checked_mul(unit)?;
+    RequestRate::new(requests, Duration::from_secs(period))
+}
+
+/// Parses the `visit-time` value, i.e. `HHMM-HHMM` or `HH:MM-HH:MM`,
+/// into the `VisitTime`.
+pub(crate) fn parse_visit_time(u: &[u8]) -> Option<VisitTime> {
+    let u = std::str::from_utf8(u).ok()?;
+    let time = |u: &str| {
+        let u = u.trim();
+        let (h, m) = match u.split_once(':') {
+            Some(split) => split,
+            None if u.is_char_boundary(2) => u.split_at(2),
+            None => return None,
+        };
+
+        let digits = |u: &str| u.len() == 2 && u.bytes().all(|u| u.is_ascii_digit());
+        match digits(h) && digits(m) {
+            true => Some((h.parse::<u8>().ok()?, m.parse::<u8>().ok()?)),
+            false => None,
+        }
+    };
+
+    let (start, end) = u.split_once('-')?;
+    VisitTime::new(time(start)?, time(end)?)
+}
+
+/// Returns the earliest time the next request may be issued at
+/// given the time of the last one (if any).
+pub(crate) fn next_request(
+    now: SystemTime,
+    last: Option<SystemTime>,
+    delay: Option<Duration>,
+    rate: Option<RequestRate>,
+    visit_times: &[VisitTime],
+) -> SystemTime {
+    let rate = rate.map(|u| u.interval());
+    let interval = max(delay.unwrap_or_default(), rate.unwrap_or_default());
+    let earliest = match last {
+        Some(last) => max(now, saturating_add(last, interval)),
+        None => now,
+    };
+
+    // Waits for the closest window to open.
+    let wait = visit_times.iter().map(|u| u.wait(earliest)).min();
+    saturating_add(earliest, wait.unwrap_or_default())
+}
+
+/// Adds the duration to the time or returns the latest representable
+/// time if the sum overflows, e.g. for `request-rate: 1/18446744073709551615s`.
+fn saturating_add(mut time: SystemTime, mut duration: Duration) -> SystemTime {
+    if let Some(u) = time.checked_add(duration) {
+        return u;
+    }
+
+    while !duration.is_zero() {
+        duration /= 2;
+        time = time.checked_add(duration).unwrap_or(time);
+    }
+
+    time
+}
+
+#[cfg(test)]
+mod scheduling {
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(60 * 60);
+
+    #[test]
+    fn request_rate() {
+        let r = parse_request_rate(b"1/10s").unwrap();
+        assert_eq!(r.interval(), Duration::from_secs(10));
+
+        let r = parse_request_rate(b"3 / 1M").unwrap();
+        assert_eq!((r.requests(), r.period()), (3, Duration::from_secs(60)));
+        assert_eq!(r.interval(), Duration::from_secs(20));
+
+        let r = parse_request_rate(b"1/5").unwrap();
+        assert_eq!(r.interval(), Duration::from_secs(5));
+
+        assert!(parse_request_rate(b"0/10s").is_none());
+        assert!(parse_request_rate(b"1/0s").is_none());
+        assert!(parse_request_rate(b"1/10y").is_none());
+        assert!(parse_request_rate(b"10s").is_none());
+    }
+
+    #[test]
+    fn visit_time() {
+        let v = parse_visit_time(b"0100-0645").unwrap();
+        assert_eq!((v.start(), v.end()), (HOUR, HOUR * 6 + HOUR / 4 * 3));
+
+        let v = parse_visit_time(b"22:00 - 02:00").unwrap();
+        assert!(v.contains(UNIX_EPOCH + HOUR * 23));
+        assert!(v.contains(UNIX_EPOCH + HOUR));
+        assert!(!v.contains(UNIX_EPOCH + HOUR * 2));
+
+        assert!(parse_visit_time(b"2400-0100").is_none());
+        assert!(parse_visit_time(b"0100-0100").is_none());
+        assert!(parse_visit_time(b"100-200").is_none());
+        assert!(parse_visit_time(b"1:00-02:00").is_none());
+        assert!(parse_visit_time("фу-0100".as_bytes()).is_none());
+    }
+
+    #[test]
+    fn next() {
+        let now = UNIX_EPOCH + HOUR * 12;
+        let delay = Some(Duration::from_secs(5));
+        let rate = parse_request_rate(b"1/10s");
+
+        assert_eq!(next_request(now, None, delay, rate, &[]), now);
+
+        let last = Some(now - Duration::from_secs(4));
+        let next = next_request(now, last, delay, rate, &[]);
+        assert_eq!(next, now + Duration::from_secs(6));
+
+        let last = Some(now - HOUR);
+        assert_eq!(next_request(now, last, delay, rate, &[]), now);
+    }
+
+    #[test]
+    fn windows() {
+        let now = UNIX_EPOCH + HOUR * 12;
+        let v = [
+            parse_visit_time(b"2200-0200").unwrap(),
+            parse_visit_time(b"1400-1500").unwrap(),
+        ];
+
+        let next = next_request(now, None, None, None, &v);
+        assert_eq!(next, now + HOUR * 2);
+
+        let now = now + HOUR * 3;
+        let next = next_request(now, None, None, None, &v);
+        assert_eq!(next, now + HOUR * 7);
+
+        let now = now + HOUR * 8 + Duration::from_millis(1);
+        assert_eq!(next_request(now, None, None, None, &v), now);
+
+        let last = Some(now);
+        let delay = Some(HOUR * 3);
+        let next = next_request(now, last, delay, None, &v);
+        assert_eq!(next, UNIX_EPOCH + HOUR * 38);
+    }
+
+    #[test]
+    fn overflow() {
+        let now = UNIX_EPOCH + HOUR * 12;
+        let rate = parse_request_rate(b"1/18446744073709551615s");
+        let delay = Some(Duration::MAX);
+        let v = [parse_visit_time(b"0100-0200").unwrap()];
+
+        let next = next_request(now, Some(now), None, rate, &[]);
+        assert!(next > now + HOUR * 24 * 365 * 1000);
+
+        let next = next_request(now, Some(now), delay, None, &[]);
+        assert!(next > now + HOUR * 24 * 365 * 1000);
+        assert!(next.checked_add(Duration::from_secs(1)).is_none());
+
+        let delayed = next_request(now, Some(now), delay, rate, &v);
+        assert!(delayed >= next - HOUR * 24);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn validated() {
+        let r = parse_request_rate(b"3/1m").unwrap();
+        let json = serde_json::to_string(&r).unwrap();
+        assert_eq!(serde_json::from_str::<RequestRate>(&json).unwrap(), r);
+
+        let v = parse_visit_time(b"2200-0200").unwrap();
+        let json = serde_json::to_string(&v).unwrap();
+        assert_eq!(serde_json::from_str::<VisitTime>(&json).unwrap(), v);
+
+        let cases = [
+            r#"{"requests":0,"period":{"secs":60,"nanos":0}}"#,
+            r#"{"requests":1,"period":{"secs":0,"nanos":0}}"#,
+        ];
+        for json in cases {
+            assert!(serde_json::from_str::<RequestRate>(json).is_err(), "{json}");
+        }
+
+        let cases = [
+            r#"{"start":{"secs":3600,"nanos":0},"end":{"secs":3600,"nanos":0}}"#,
+            r#"{"start":{"secs":0,"nanos":0},"end":{"secs":86400,"nanos":0}}"#,
+        ];
+        for json in cases {
+            assert!(serde_json::from_str::<VisitTime>(json).is_err(), "{json}");
+        }
+    }
+}
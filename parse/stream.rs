@@ -0,0 +1,484 @@
+use std::borrow::Cow;
+use std::mem::take;
+use std::ops::ControlFlow;
+
+use crate::parse::{b_strip_bom, detect, into_token, Charset, Collector, Dialect, Document};
+use crate::parse::{ParserOptions, Token, Transcoder};
+
+/// Returns the length of the first complete line including its ending (if any).
+/// NOTE: The trailing carriage return is complete only at the end of input,
+/// as it may be followed by the line feed in the next chunk.
+fn line_end(input: &[u8], last: bool) -> Option<usize> {
+    let i = input.iter().position(|u| b"\r\n\x00".contains(u))?;
+    match (input[i], input.get(i + 1)) {
+        (b'\r', Some(b'\n')) => Some(i + 2),
+        (b'\r', None) if !last => None,
+        _ => Some(i + 1),
+    }
+}
+
+/// The `StreamLexer` struct provides the push-based lexer that accepts
+/// the input in chunks, e.g. as they arrive off the socket, and emits
+/// every directive as soon as its line is complete.
+///
+/// ```rust
+/// use std::ops::ControlFlow;
+///
+/// use robotxt::{Directive, ParserOptions, StreamLexer};
+///
+/// let mut lexer = StreamLexer::new(&ParserOptions::default());
+/// let mut directives = Vec::new();
+/// for chunk in [&b"User-Agent: foo"[..], b"bot\nDisallow: /exa", b"mple/"] {
+///     let _ = lexer.push(chunk, |u| {
+///         directives.push(format!("{:?}", u.directive()));
+///         ControlFlow::Continue(())
+///     });
+/// }
+///
+/// let _ = lexer.finish(|u| {
+///     directives.push(format!("{:?}", u.directive()));
+///     ControlFlow::Continue(())
+/// });
+///
+/// assert_eq!(directives, ["User-Agent(\"foobot\")", "Disallow(\"/example/\")"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct StreamLexer {
+    dialect: Dialect,
+    limit: usize,
+    cut_at_line: bool,
+    charset: Option<Charset>,
+    // The start of input until the byte order mark is detected.
+    head: Option<Vec<u8>>,
+    transcoder: Option<Transcoder>,
+    // The incomplete line.
+    buffer: Vec<u8>,
+    received: usize,
+    offset: usize,
+    line: usize,
+    truncated: Option<usize>,
+    stopped: bool,
+}
+
+impl StreamLexer {
+    /// Creates a new `StreamLexer` with the specified options.
+    pub fn new(options: &ParserOptions) -> Self {
+        Self {
+            dialect: options.dialect,
+            limit: options.limit,
+            cut_at_line: options.cut_at_line,
+            charset: options.charset,
+            head: Some(Vec::new()),
+            transcoder: None,
+            buffer: Vec::new(),
+            received: 0,
+            offset: 0,
+            line: 0,
+            truncated: None,
+            stopped: false,
+        }
+    }
+
+    /// Lexes the complete line and passes the token to the function.
+    fn emit<F>(&mut self, line: &[u8], f: &mut F) -> ControlFlow<()>
+    where
+        F: FnMut(Token<'_>) -> ControlFlow<()>,
+    {
+        // Removes the byte order mark (BOM) at the start of input.
+        let bom = match self.offset {
+            0 => line.len() - b_strip_bom(line).len(),
+            _ => 0,
+        };
+
+        let offset = self.offset + bom;
+        self.offset += line.len();
+        if line.len() == bom {
+            return ControlFlow::Continue(());
+        }
+
+        self.line += 1;
+        let token = into_token(&line[bom..], self.line, offset, self.dialect);
+        if f(token).is_break() {
+            self.stopped = true;
+            return ControlFlow::Break(());
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    /// Transcodes the chunk into UTF-8 if the input is not UTF-8.
+    /// Returns `None` while the byte order mark might be incomplete.
+    /// See [ParserOptions::charset].
+    fn transcode<'c>(&mut self, chunk: &'c [u8], last: bool) -> Option<Cow<'c, [u8]>> {
+        let chunk = match self.head.take() {
+            Some(mut head) => {
+                head.extend_from_slice(chunk);
+                if head.len() < 4 && !last {
+                    self.head = Some(head);
+                    return None;
+                }
+
+                let (charset, bom) = detect(&head, self.charset);
+                self.transcoder = charset.map(Transcoder::new);
+                head.drain(..bom);
+                Cow::Owned(head)
+            }
+            None => Cow::Borrowed(chunk),
+        };
+
+        let transcoder = match self.transcoder.as_mut() {
+            Some(transcoder) => transcoder,
+            None => return Some(chunk),
+        };
+
+        let mut output = Vec::with_capacity(chunk.len());
+        transcoder.push(&chunk, last, &mut output);
+        Some(Cow::Owned(output))
+    }
+
+    /// Lexes every complete line of the chunk and passes the tokens
+    /// to the function until it breaks. Returns `Break` if the lexer
+    /// does not need any more input, i.e. was stopped or truncated.
+    /// NOTE: The incomplete line is kept until the next chunk.
+    pub fn push<F>(&mut self, chunk: &[u8], f: F) -> ControlFlow<()>
+    where
+        F: FnMut(Token<'_>) -> ControlFlow<()>,
+    {
+        if self.stopped || self.truncated.is_some() {
+            return ControlFlow::Break(());
+        }
+
+        match self.transcode(chunk, false) {
+            Some(chunk) => self.push_utf8(&chunk, f),
+            None => ControlFlow::Continue(()),
+        }
+    }
+
+    /// Lexes every complete line of the UTF-8 chunk.
+    /// See [StreamLexer::push].
+    fn push_utf8<F>(&mut self, chunk: &[u8], mut f: F) -> ControlFlow<()>
+    where
+        F: FnMut(Token<'_>) -> ControlFlow<()>,
+    {
+        // Ignores everything beyond the file size limit.
+        let room = self.limit - self.received;
+        let chunk = match chunk.len() > room {
+            true => {
+                self.truncated = Some(self.limit);
+                &chunk[..room]
+            }
+            false => chunk,
+        };
+
+        self.received += chunk.len();
+        let mut chunk = chunk;
+
+        // Completes the line started in the previous chunks.
+        if !self.buffer.is_empty() && !chunk.is_empty() {
+            let end = match self.buffer.last() {
+                Some(b'\r') if chunk[0] == b'\n' => Some(1),
+                Some(b'\r') => Some(0),
+                _ => line_end(chunk, false),
+            };
+
+            let end = match end {
+                Some(end) => end,
+                None => {
+                    self.buffer.extend_from_slice(chunk);
+                    return self.flow();
+                }
+            };
+
+            let mut line = take(&mut self.buffer);
+            line.extend_from_slice(&chunk[..end]);
+            self.emit(&line, &mut f)?;
+
+            // Keeps the allocated buffer.
+            line.clear();
+            self.buffer = line;
+            chunk = &chunk[end..];
+        }
+
+        // Lexes the lines contained in the chunk without copying.
+        while let Some(end) = line_end(chunk, false) {
+            self.emit(&chunk[..end], &mut f)?;
+            chunk = &chunk[end..];
+        }
+
+        self.buffer.extend_from_slice(chunk);
+        self.flow()
+    }
+
+    /// Returns `Break` if the lexer does not need any more input.
+    fn flow(&self) -> ControlFlow<()> {
+        match self.truncated.is_some() {
+            true => ControlFlow::Break(()),
+            false => ControlFlow::Continue(()),
+        }
+    }
+
+    /// Lexes the last line (if any) as the end of input was reached.
+    /// NOTE: The incomplete line is dropped if the oversized input
+    /// should be cut at the end of the last full line.
+    pub fn finish<F>(&mut self, mut f: F) -> ControlFlow<()>
+    where
+        F: FnMut(Token<'_>) -> ControlFlow<()>,
+    {
+        if self.stopped {
+            return ControlFlow::Break(());
+        }
+
+        // Flushes the undetected or incomplete sequence at the end of input.
+        if self.truncated.is_none() {
+            if let Some(chunk) = self.transcode(b"", true) {
+                let _ = self.push_utf8(&chunk, &mut f);
+            }
+
+            if self.stopped {
+                return ControlFlow::Break(());
+            }
+        }
+
+        let line = take(&mut self.buffer);
+        let complete = line_end(&line, true).is_some();
+        if self.truncated.is_some() && self.cut_at_line && !complete {
+            self.truncated = Some(self.offset);
+            return ControlFlow::Continue(());
+        }
+
+        if !line.is_empty() {
+            self.emit(&line, &mut f)?;
+        }
+
+        if self.cut_at_line && self.truncated.is_some() {
+            self.truncated = Some(self.offset);
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    /// Returns true if the function stopped the lexer.
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Returns the byte the oversized input was truncated at (if any).
+    /// NOTE: Final only after [StreamLexer::finish].
+    pub fn truncated(&self) -> Option<usize> {
+        self.truncated
+    }
+
+    /// Returns the byte the first malformed sequence
+    /// was replaced at while transcoding (if any).
+    /// NOTE: Final only after [StreamLexer::finish].
+    pub fn replaced(&self) -> Option<usize> {
+        let replaced = self.transcoder.as_ref()?.replaced();
+        replaced.filter(|u| self.truncated.is_none_or(|t| *u < t))
+    }
+}
+
+/// The `StreamParser` struct provides the push-based parser of the
+/// `Document` that accepts the input in chunks and can stop early,
+/// e.g. once all groups relevant to the crawler are known.
+///
+/// ```rust
+/// use std::ops::ControlFlow;
+///
+/// use robotxt::{Directive, StreamParser};
+///
+/// let mut parser = StreamParser::default();
+/// let _ = parser.push(b"User-Agent: foobot\nDisallow: /example/\n");
+///
+/// // Stops at the next group.
+/// let flow = parser.push_until(b"User-Agent: barbot\nDisallow: /\n", |u| {
+///     match matches!(u.directive(), Directive::UserAgent(_)) {
+///         true => ControlFlow::Break(()),
+///         false => ControlFlow::Continue(()),
+///     }
+/// });
+///
+/// assert!(flow.is_break());
+/// let doc = parser.finish();
+/// assert!(!doc.robots("foobot").is_allowed("/example/nope.txt"));
+/// assert!(doc.robots("barbot").is_allowed("/example/yeah.txt"));
+/// ```
+#[derive(Debug)]
+pub struct StreamParser {
+    lexer: StreamLexer,
+    collector: Collector,
+}
+
+impl Default for StreamParser {
+    fn default() -> Self {
+        Self::new(&ParserOptions::default())
+    }
+}
+
+impl StreamParser {
+    /// Creates a new `StreamParser` with the specified options.
+    pub fn new(options: &ParserOptions) -> Self {
+        Self {
+            lexer: StreamLexer::new(options),
+            collector: Collector::new(options.dialect),
+        }
+    }
+
+    /// Parses every complete line of the chunk.
+    /// Returns `Break` if the parser does not need any more input.
+    pub fn push(&mut self, chunk: &[u8]) -> ControlFlow<()> {
+        self.push_until(chunk, |_| ControlFlow::Continue(()))
+    }
+
+    /// Parses every complete line of the chunk until the function breaks.
+    /// Returns `Break` if the parser does not need any more input.
+    /// NOTE: The directive the function breaks at is included.
+    pub fn push_until<F>(&mut self, chunk: &[u8], mut f: F) -> ControlFlow<()>
+    where
+        F: FnMut(&Token<'_>) -> ControlFlow<()>,
+    {
+        let collector = &mut self.collector;
+        self.lexer.push(chunk, |u| {
+            collector.push(&u);
+            f(&u)
+        })
+    }
+
+    /// Returns true if the parser was stopped early.
+    pub fn is_stopped(&self) -> bool {
+        self.lexer.is_stopped()
+    }
+
+    /// Parses the last line (if any) and returns the `Document`.
+    pub fn finish(mut self) -> Document {
+        let collector = &mut self.collector;
+        let _ = self.lexer.finish(|u| {
+            collector.push(&u);
+            ControlFlow::Continue(())
+        });
+
+        let (truncated, replaced) = (self.lexer.truncated(), self.lexer.replaced());
+        self.collector.finish(truncated, replaced)
+    }
+}
+
+#[cfg(test)]
+mod streaming {
+    use super::*;
+    use crate::parse::{into_directives_with, BYTES_LIMIT};
+
+    static TXT: &[u8] = b"\xef\xbb\xbfUser-Agent: foobot\r\n\
+        # comment\r\rDisallow: /a # b\x00\
+        \n\
+        Allow: /a/b\r\n\
+        User-Agent: *\r\
+        Disallow: /c";
+
+    /// Lexes the input in chunks of the specified size.
+    fn chunked(input: &[u8], size: usize, options: &ParserOptions) -> (Vec<String>, Option<usize>) {
+        let mut tokens = Vec::new();
+        let mut lexer = StreamLexer::new(options);
+        let mut f = |u: Token<'_>| {
+            tokens.push(format!("{u:?}"));
+            ControlFlow::Continue(())
+        };
+
+        for chunk in input.chunks(size) {
+            let _ = lexer.push(chunk, &mut f);
+        }
+
+        let _ = lexer.finish(&mut f);
+        (tokens, lexer.truncated())
+    }
+
+    #[test]
+    fn chunks() {
+        let expected = into_directives_with(TXT, Dialect::default());
+        let expected: Vec<_> = expected.iter().map(|u| format!("{u:?}")).collect();
+
+        for size in 1..=TXT.len() {
+            let (tokens, truncated) = chunked(TXT, size, &ParserOptions::default());
+            assert_eq!(tokens, expected, "{size}");
+            assert_eq!(truncated, None);
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        let mut txt = b"User-Agent: *\r\n".to_vec();
+        txt.extend(b"Disallow: /a\r\n".repeat(BYTES_LIMIT / 10));
+
+        for cut_at_line in [false, true] {
+            let options = ParserOptions::default().cut_at_line(cut_at_line);
+            let (prepared, truncated) = options.prepare(&txt);
+            let expected = into_directives_with(prepared, Dialect::default());
+            let expected: Vec<_> = expected.iter().map(|u| format!("{u:?}")).collect();
+
+            for size in [1000, 4096, 65536] {
+                let (tokens, found) = chunked(&txt, size, &options);
+                assert_eq!(found, truncated, "{size} {cut_at_line}");
+                assert!(tokens == expected, "{size} {cut_at_line}");
+            }
+        }
+    }
+
+    #[test]
+    fn transcoded() {
+        let txt = String::from_utf8_lossy(&TXT[3..]);
+        let units = [0xfeff].into_iter().chain(txt.encode_utf16());
+        let utf16: Vec<_> = units.flat_map(|u| u.to_be_bytes()).collect();
+
+        let expected = into_directives_with(&TXT[3..], Dialect::default());
+        let expected: Vec<_> = expected.iter().map(|u| format!("{u:?}")).collect();
+        for size in 1..=utf16.len() {
+            let (tokens, _) = chunked(&utf16, size, &ParserOptions::default());
+            assert_eq!(tokens, expected, "{size}");
+        }
+    }
+
+    #[test]
+    fn cloned() {
+        let units = "Disallow: /фу\n".encode_utf16();
+        let utf16: Vec<_> = units.flat_map(|u| u.to_le_bytes()).collect();
+        let options = ParserOptions::default().content_type("text/plain; charset=utf-16le");
+        let lex = |lexer: &mut StreamLexer, chunk: &[u8]| {
+            let mut tokens = Vec::new();
+            let _ = lexer.push(chunk, |u| {
+                tokens.push(format!("{:?}", u.directive()));
+                ControlFlow::Continue(())
+            });
+            let _ = lexer.finish(|u| {
+                tokens.push(format!("{:?}", u.directive()));
+                ControlFlow::Continue(())
+            });
+            (tokens, lexer.replaced())
+        };
+
+        // Clones the lexer in the middle of the character.
+        for at in 0..utf16.len() {
+            let mut lexer = StreamLexer::new(&options);
+            let _ = lexer.push(&utf16[..at], |_| ControlFlow::Continue(()));
+            let mut cloned = lexer.clone();
+            let expected = lex(&mut lexer, &utf16[at..]);
+            assert_eq!(lex(&mut cloned, &utf16[at..]), expected, "{at}");
+            assert_eq!(expected.0, vec!["Disallow(\"/фу\")".to_string()]);
+            assert_eq!(expected.1, None);
+        }
+    }
+
+    #[test]
+    fn stopped() {
+        let mut parser = StreamParser::default();
+        let flow = parser.push_until(TXT, |u| match u.position().line() {
+            4 => ControlFlow::Break(()),
+            _ => ControlFlow::Continue(()),
+        });
+
+        assert!(flow.is_break() && parser.is_stopped());
+        assert!(parser.push(b"\nDisallow: /").is_break());
+
+        let doc = parser.finish();
+        assert!(!doc.robots("foobot").is_allowed("/a"));
+        assert!(doc.robots("foobot").is_allowed("/c"));
+        assert_eq!(doc.groups().len(), 1);
+    }
+}
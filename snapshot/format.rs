@@ -0,0 +1,123 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::parse::Dialect;
+
+/// The magic bytes every snapshot starts with.
+pub(crate) const MAGIC: &[u8; 4] = b"RTXT";
+
+/// The current version of the snapshot format.
+/// NOTE: Snapshots of any other version are rejected.
+pub const SNAPSHOT_VERSION: u16 = 2;
+
+/// The header layout: magic (4), version (2), dialect (1), reserved (1),
+/// payload length (4) and payload checksum (4).
+pub(crate) const HEADER_LEN: usize = 16;
+
+/// The payload preamble layout: number of agents (4), rules (4),
+/// sitemaps (4), the truncation byte (8), number of visit-times (4),
+/// clean-params (4), their parameters (4) and the host (4 + 4).
+pub(crate) const PREAMBLE_LEN: usize = 40;
+
+/// The agent record layout: key (4 + 4), first rule (4), number of rules (4),
+/// crawl-delay seconds (8) and nanoseconds (4), request-rate requests (4),
+/// period seconds (8) and nanoseconds (4), first visit-time (4) and
+/// number of visit-times (4).
+pub(crate) const AGENT_LEN: usize = 52;
+
+/// The position layout: presence (4), line (4), line offset (4),
+/// key span (4 + 4) and value span (4 + 4).
+pub(crate) const POSITION_LEN: usize = 28;
+
+/// The rule record layout: pattern (4 + 4), permission (1),
+/// padding (3) and position.
+pub(crate) const RULE_LEN: usize = 12 + POSITION_LEN;
+
+/// The visit-time record layout: window start (8) and end (8)
+/// in nanoseconds since midnight.
+pub(crate) const VISIT_TIME_LEN: usize = 16;
+
+/// The clean-param record layout: path (4 + 4), first parameter (4),
+/// number of parameters (4) and position.
+pub(crate) const CLEAN_PARAM_LEN: usize = 16 + POSITION_LEN;
+
+/// The parameter record layout: parameter (4 + 4).
+pub(crate) const PARAM_LEN: usize = 8;
+
+/// The sitemap record layout: URL (4 + 4).
+pub(crate) const SITEMAP_LEN: usize = 8;
+
+/// The sentinel of the missing optional values.
+pub(crate) const NONE_U32: u32 = u32::MAX;
+pub(crate) const NONE_U64: u64 = u64::MAX;
+
+/// An error type indicating that the snapshot could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// The input does not start with the snapshot magic bytes.
+    Magic,
+    /// The snapshot was written with the unsupported format version.
+    Version { expected: u16, found: u16 },
+    /// The input is shorter or longer than the header says.
+    Length { expected: usize, found: usize },
+    /// The payload does not match the header checksum.
+    Checksum { expected: u32, found: u32 },
+    /// The payload has out of bounds references or invalid strings.
+    Malformed,
+    /// The document exceeds the 4 GiB limit of the format.
+    Overflow,
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self {
+            Self::Magic => write!(f, "input is not a robots.txt snapshot"),
+            Self::Version { expected, found } => write!(
+                f,
+                "snapshot version {found} is not supported, expected {expected}"
+            ),
+            Self::Length { expected, found } => write!(
+                f,
+                "snapshot length {found} differs from the expected {expected}"
+            ),
+            Self::Checksum { expected, found } => write!(
+                f,
+                "snapshot checksum {found:#010x} differs from the expected {expected:#010x}"
+            ),
+            Self::Malformed => write!(f, "snapshot payload is malformed"),
+            Self::Overflow => write!(f, "document exceeds the 4 GiB snapshot limit"),
+        }
+    }
+}
+
+impl Error for SnapshotError {}
+
+/// Returns the 32-bit FNV-1a hash of the payload.
+pub(crate) fn checksum(payload: &[u8]) -> u32 {
+    payload.iter().fold(0x811c9dc5, |hash, u| {
+        (hash ^ *u as u32).wrapping_mul(0x01000193)
+    })
+}
+
+/// Returns the byte the dialect is stored as.
+pub(crate) fn dialect_to_byte(dialect: Dialect) -> u8 {
+    match dialect {
+        Dialect::Lenient => 0,
+        Dialect::Standard => 1,
+        Dialect::Google => 2,
+        Dialect::Bing => 3,
+        Dialect::Yandex => 4,
+    }
+}
+
+/// Returns the dialect stored as the byte (if valid).
+pub(crate) fn dialect_from_byte(u: u8) -> Option<Dialect> {
+    match u {
+        0 => Some(Dialect::Lenient),
+        1 => Some(Dialect::Standard),
+        2 => Some(Dialect::Google),
+        3 => Some(Dialect::Bing),
+        4 => Some(Dialect::Yandex),
+        _ => None,
+    }
+}
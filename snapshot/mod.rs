@@ -0,0 +1,6 @@
+mod format;
+mod reader;
+mod writer;
+
+pub use format::*;
+pub use reader::*;
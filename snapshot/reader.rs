@@ -0,0 +1,522 @@
+use std::time::{Duration, SystemTime};
+
+use crate::parse::ROBOTS_TXT;
+use crate::parse::{match_pattern, next_request, normalize_path, parse_host, CleanParam};
+use crate::parse::{Dialect, Host, Position, RequestRate, Robots, Rule, Span, VisitTime};
+use crate::snapshot::{checksum, dialect_from_byte, SnapshotError, SNAPSHOT_VERSION};
+use crate::snapshot::{AGENT_LEN, HEADER_LEN, MAGIC, NONE_U32, NONE_U64, PREAMBLE_LEN};
+use crate::snapshot::{CLEAN_PARAM_LEN, PARAM_LEN, RULE_LEN, SITEMAP_LEN, VISIT_TIME_LEN};
+
+/// The number of nanoseconds in the second.
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+/// Returns the little-endian `u32` at the offset.
+/// NOTE: Expects the offset to be validated.
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    let mut u = [0; 4];
+    u.copy_from_slice(&bytes[offset..offset + 4]);
+    u32::from_le_bytes(u)
+}
+
+/// Returns the little-endian `u64` at the offset.
+/// NOTE: Expects the offset to be validated.
+fn u64_at(bytes: &[u8], offset: usize) -> u64 {
+    let mut u = [0; 8];
+    u.copy_from_slice(&bytes[offset..offset + 8]);
+    u64::from_le_bytes(u)
+}
+
+/// Returns the duration of the seconds and nanoseconds at the offset.
+/// NOTE: Expects the nanoseconds to be validated.
+fn duration_at(bytes: &[u8], offset: usize) -> Duration {
+    Duration::new(u64_at(bytes, offset), u32_at(bytes, offset + 8))
+}
+
+/// Returns the position at the offset (if any).
+fn position_at(bytes: &[u8], offset: usize) -> Option<Position> {
+    if u32_at(bytes, offset) == 0 {
+        return None;
+    }
+
+    let u = |i: usize| u32_at(bytes, offset + i * 4) as usize;
+    let (key, value) = (Span::new(u(3), u(4)), Span::new(u(5), u(6)));
+    Some(Position::new(u(1), u(2), key, value))
+}
+
+/// Returns the visit-time at the offset (if valid).
+fn visit_time_at(bytes: &[u8], offset: usize) -> Option<VisitTime> {
+    let start = Duration::from_nanos(u64_at(bytes, offset));
+    let end = Duration::from_nanos(u64_at(bytes, offset + 8));
+    VisitTime::from_window(start, end)
+}
+
+/// The `SnapshotRef` struct provides the queries over the binary snapshot
+/// without deserializing it, e.g. over the memory-mapped file.
+/// See [crate::Document::to_snapshot].
+///
+/// ```rust
+/// use robotxt::{Document, SnapshotRef};
+///
+/// let txt = b"User-Agent: foobot\nDisallow: /example/\nCrawl-Delay: 5";
+/// let bytes = Document::from_slice(txt).to_snapshot().unwrap();
+///
+/// let snapshot = SnapshotRef::new(&bytes).unwrap();
+/// assert!(!snapshot.is_allowed("foobot", "/example/nope.txt"));
+/// assert!(snapshot.is_allowed("barbot", "/example/yeah.txt"));
+/// assert_eq!(snapshot.crawl_delay("foobot").unwrap().as_secs(), 5);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct SnapshotRef<'a> {
+    dialect: Dialect,
+    agents: &'a [u8],
+    rules: &'a [u8],
+    visit_times: &'a [u8],
+    clean_params: &'a [u8],
+    params: &'a [u8],
+    sitemaps: &'a [u8],
+    strings: &'a str,
+    host: Option<&'a str>,
+    truncated: Option<usize>,
+}
+
+impl<'a> SnapshotRef<'a> {
+    /// Creates a new `SnapshotRef` over the bytes after validating
+    /// the header, the checksum and all references of the payload.
+    pub fn new(bytes: &'a [u8]) -> Result<Self, SnapshotError> {
+        if bytes.len() < HEADER_LEN || !bytes.starts_with(MAGIC) {
+            return Err(SnapshotError::Magic);
+        }
+
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != SNAPSHOT_VERSION {
+            let (expected, found) = (SNAPSHOT_VERSION, version);
+            return Err(SnapshotError::Version { expected, found });
+        }
+
+        let length = u32_at(bytes, 8) as usize;
+        let (expected, found) = (HEADER_LEN + length, bytes.len());
+        if expected != found {
+            return Err(SnapshotError::Length { expected, found });
+        }
+
+        let payload = &bytes[HEADER_LEN..];
+        let (expected, found) = (u32_at(bytes, 12), checksum(payload));
+        if expected != found {
+            return Err(SnapshotError::Checksum { expected, found });
+        }
+
+        let dialect = dialect_from_byte(bytes[6]).ok_or(SnapshotError::Malformed)?;
+        Self::from_payload(payload, dialect).ok_or(SnapshotError::Malformed)
+    }
+
+    /// Splits the payload into the record tables and validates them.
+    fn from_payload(payload: &'a [u8], dialect: Dialect) -> Option<Self> {
+        let preamble = payload.get(..PREAMBLE_LEN)?;
+        let agents = u32_at(preamble, 0) as usize * AGENT_LEN;
+        let rules = u32_at(preamble, 4) as usize * RULE_LEN;
+        let sitemaps = u32_at(preamble, 8) as usize * SITEMAP_LEN;
+        let truncated = match u64_at(preamble, 12) {
+            NONE_U64 => None,
+            u => Some(usize::try_from(u).ok()?),
+        };
+
+        let visit_times = u32_at(preamble, 20) as usize * VISIT_TIME_LEN;
+        let clean_params = u32_at(preamble, 24) as usize * CLEAN_PARAM_LEN;
+        let params = u32_at(preamble, 28) as usize * PARAM_LEN;
+
+        let rest = &payload[PREAMBLE_LEN..];
+        let (agents, rest) = (rest.get(..agents)?, rest.get(agents..)?);
+        let (rules, rest) = (rest.get(..rules)?, rest.get(rules..)?);
+        let (visit_times, rest) = (rest.get(..visit_times)?, rest.get(visit_times..)?);
+        let (clean_params, rest) = (rest.get(..clean_params)?, rest.get(clean_params..)?);
+        let (params, rest) = (rest.get(..params)?, rest.get(params..)?);
+        let (sitemaps, rest) = (rest.get(..sitemaps)?, rest.get(sitemaps..)?);
+        let strings = std::str::from_utf8(rest).ok()?;
+
+        let mut snapshot = Self {
+            dialect,
+            agents,
+            rules,
+            visit_times,
+            clean_params,
+            params,
+            sitemaps,
+            strings,
+            host: None,
+            truncated,
+        };
+
+        snapshot.host = match u32_at(preamble, 32) {
+            NONE_U32 => None,
+            _ => Some(snapshot.string_within(preamble, 32)?),
+        };
+
+        snapshot.validate().then_some(snapshot)
+    }
+
+    /// Returns the string the record references (if within bounds).
+    fn string_within(&self, table: &[u8], offset: usize) -> Option<&'a str> {
+        let start = u32_at(table, offset) as usize;
+        let end = start.checked_add(u32_at(table, offset + 4) as usize)?;
+        self.strings.get(start..end)
+    }
+
+    /// Returns true if all references are within bounds
+    /// and all values are valid.
+    fn validate(&self) -> bool {
+        let string = |table: &[u8], offset| self.string_within(table, offset).is_some();
+        let range = |table: &[u8], offset, count| {
+            let start = u32_at(table, offset) as usize;
+            let end = start.checked_add(u32_at(table, offset + 4) as usize);
+            end.is_some_and(|end| end <= count)
+        };
+
+        let rule_count = self.rules.len() / RULE_LEN;
+        let visit_count = self.visit_times.len() / VISIT_TIME_LEN;
+        let agents = (0..self.agents.len()).step_by(AGENT_LEN).all(|u| {
+            let delay = u32_at(self.agents, u + 24);
+            let delay = delay == NONE_U32 || delay < NANOS_PER_SEC;
+            let rate = match u32_at(self.agents, u + 28) {
+                0 => true,
+                requests => {
+                    let period = u32_at(self.agents, u + 40) < NANOS_PER_SEC;
+                    period && RequestRate::new(requests, duration_at(self.agents, u + 32)).is_some()
+                }
+            };
+
+            string(self.agents, u)
+                && range(self.agents, u + 8, rule_count)
+                && range(self.agents, u + 44, visit_count)
+                && delay
+                && rate
+        });
+
+        let mut rules = (0..self.rules.len()).step_by(RULE_LEN);
+        let rules = rules.all(|u| string(self.rules, u));
+        let mut visit_times = (0..self.visit_times.len()).step_by(VISIT_TIME_LEN);
+        let visit_times = visit_times.all(|u| visit_time_at(self.visit_times, u).is_some());
+
+        let param_count = self.params.len() / PARAM_LEN;
+        let mut clean_params = (0..self.clean_params.len()).step_by(CLEAN_PARAM_LEN);
+        let clean_params = clean_params
+            .all(|u| string(self.clean_params, u) && range(self.clean_params, u + 8, param_count));
+
+        let mut params = (0..self.params.len()).step_by(PARAM_LEN);
+        let params = params.all(|u| string(self.params, u));
+        let mut sitemaps = (0..self.sitemaps.len()).step_by(SITEMAP_LEN);
+        let sitemaps = sitemaps.all(|u| string(self.sitemaps, u));
+        let host = self.host.is_none_or(|u| parse_host(u.as_bytes()).is_some());
+
+        let tables = rules && visit_times && clean_params && params && sitemaps;
+        agents && tables && host && !self.agents.is_empty()
+    }
+
+    /// Returns the string the record references.
+    fn string(&self, table: &[u8], offset: usize) -> &'a str {
+        let start = u32_at(table, offset) as usize;
+        let end = start + u32_at(table, offset + 4) as usize;
+        &self.strings[start..end]
+    }
+
+    /// Returns the offset of the agent record of the longest matching user-agent.
+    fn agent(&self, user_agent: &str) -> usize {
+        let keys = self.user_agents();
+        let (key, _) = Robots::find_agent(keys, user_agent, self.dialect);
+
+        // Falls back to the default user-agent record.
+        let records = (0..self.agents.len()).step_by(AGENT_LEN);
+        let mut records = records.filter(|u| self.string(self.agents, *u).eq(key.as_str()));
+        records.next().unwrap_or(0)
+    }
+
+    /// Returns the interpretation the file was parsed with.
+    pub fn dialect(&self) -> Dialect {
+        self.dialect
+    }
+
+    /// Returns all user-agents in the form suitable for matching.
+    /// NOTE: The default user-agent always comes first.
+    pub fn user_agents(&self) -> impl Iterator<Item = &'a str> + '_ {
+        let records = (0..self.agents.len()).step_by(AGENT_LEN);
+        records.map(|u| self.string(self.agents, u))
+    }
+
+    /// Returns the offsets of the rule records of the user-agent.
+    fn rule_records(&self, user_agent: &str) -> impl Iterator<Item = usize> {
+        let agent = self.agent(user_agent);
+        let start = u32_at(self.agents, agent + 8) as usize;
+        let count = u32_at(self.agents, agent + 12) as usize;
+        (start..start + count).map(|u| u * RULE_LEN)
+    }
+
+    /// Returns all rules of the user-agent sorted by precedence.
+    pub fn rules(&self, user_agent: &str) -> impl Iterator<Item = Rule> + '_ {
+        self.rule_records(user_agent).map(|u| {
+            let rule = Rule::new(self.string(self.rules, u), self.rules[u + 8] == 1);
+            match position_at(self.rules, u + 12) {
+                Some(position) => rule.with_position(position),
+                None => rule,
+            }
+        })
+    }
+
+    /// Returns true if the path is allowed for the user-agent.
+    /// NOTE: Expects relative path.
+    pub fn is_allowed(&self, user_agent: &str, path: &str) -> bool {
+        let path = normalize_path(path);
+        if path.eq(ROBOTS_TXT) {
+            return true;
+        }
+
+        // Rules are stored sorted by precedence.
+        for rule in self.rule_records(user_agent) {
+            if match_pattern(self.string(self.rules, rule), path.as_str()) {
+                return self.rules[rule + 8] == 1;
+            }
+        }
+
+        true
+    }
+
+    /// Returns the crawl-delay of the user-agent.
+    pub fn crawl_delay(&self, user_agent: &str) -> Option<Duration> {
+        let agent = self.agent(user_agent);
+        match u32_at(self.agents, agent + 24) {
+            NONE_U32 => None,
+            _ => Some(duration_at(self.agents, agent + 16)),
+        }
+    }
+
+    /// Returns the request-rate of the user-agent.
+    pub fn request_rate(&self, user_agent: &str) -> Option<RequestRate> {
+        let agent = self.agent(user_agent);
+        match u32_at(self.agents, agent + 28) {
+            0 => None,
+            requests => RequestRate::new(requests, duration_at(self.agents, agent + 32)),
+        }
+    }
+
+    /// Returns the visit-time windows of the user-agent.
+    pub fn visit_times(&self, user_agent: &str) -> impl Iterator<Item = VisitTime> + '_ {
+        let agent = self.agent(user_agent);
+        let start = u32_at(self.agents, agent + 44) as usize;
+        let count = u32_at(self.agents, agent + 48) as usize;
+        let records = (start..start + count).map(|u| u * VISIT_TIME_LEN);
+        records.filter_map(|u| visit_time_at(self.visit_times, u))
+    }
+
+    /// Returns the earliest UTC time the next request of the user-agent may
+    /// be issued at given the current time and the time of the last request.
+    /// See [Robots::next_request].
+    pub fn next_request(
+        &self,
+        user_agent: &str,
+        now: SystemTime,
+        last: Option<SystemTime>,
+    ) -> SystemTime {
+        let delay = self.crawl_delay(user_agent);
+        let rate = self.request_rate(user_agent);
+        let visit_times: Vec<_> = self.visit_times(user_agent).collect();
+        next_request(now, last, delay, rate, &visit_times)
+    }
+
+    /// Returns all sitemaps.
+    pub fn sitemaps(&self) -> impl Iterator<Item = &'a str> + '_ {
+        let records = (0..self.sitemaps.len()).step_by(SITEMAP_LEN);
+        records.map(|u| self.string(self.sitemaps, u))
+    }
+
+    /// Returns all clean-params.
+    pub fn clean_params(&self) -> impl Iterator<Item = CleanParam> + '_ {
+        let records = (0..self.clean_params.len()).step_by(CLEAN_PARAM_LEN);
+        records.map(|u| {
+            let start = u32_at(self.clean_params, u + 8) as usize;
+            let count = u32_at(self.clean_params, u + 12) as usize;
+            let params = (start..start + count).map(|u| self.string(self.params, u * PARAM_LEN));
+            let params: Vec<_> = params.collect();
+
+            let clean_param = CleanParam::new(&params, self.string(self.clean_params, u));
+            match position_at(self.clean_params, u + 16) {
+                Some(position) => clean_param.with_position(position),
+                None => clean_param,
+            }
+        })
+    }
+
+    /// Returns the preferred mirror (if specified).
+    pub fn host(&self) -> Option<Host> {
+        self.host.and_then(|u| parse_host(u.as_bytes()))
+    }
+
+    /// Returns the byte the oversized input was truncated at (if any).
+    pub fn truncated(&self) -> Option<usize> {
+        self.truncated
+    }
+}
+
+#[cfg(test)]
+mod snapshots {
+    use super::*;
+    use crate::parse::{Document, ParserOptions};
+
+    static TXT: &[u8] = b"Disallow: /0\n\
+        User-Agent: foobot\n\
+        User-Agent: barbot\n\
+        Disallow: /1\n\
+        Crawl-Delay: 1.5\n\
+        Request-Rate: 1/10s\n\
+        Visit-Time: 2200-0200\n\
+        User-Agent: foobot\n\
+        Allow: /1/a\n\
+        Disallow: /*.php$\n\
+        Sitemap: https://example.com/sitemap.xml\n\
+        User-Agent: *\n\
+        Disallow: /3\n\
+        Clean-Param: ref&utm /articles/\n\
+        Host: https://example.com\n";
+
+    #[test]
+    fn queries() {
+        let d = Document::from_slice(TXT);
+        let bytes = d.to_snapshot().unwrap();
+        let s = SnapshotRef::new(&bytes).unwrap();
+
+        let uas: Vec<_> = s.user_agents().collect();
+        assert_eq!(uas, vec!["*", "foobot", "barbot"]);
+
+        let paths = ["/0", "/1", "/1/a", "/2.php", "/2.php5", "/3", "/robots.txt"];
+        for ua in ["foobot", "barbot", "foobot-image", "nobot"] {
+            let r = d.robots(ua);
+            for path in paths {
+                assert_eq!(s.is_allowed(ua, path), r.is_allowed(path), "{ua} {path}");
+            }
+
+            assert_eq!(s.crawl_delay(ua), r.crawl_delay());
+            assert_eq!(s.request_rate(ua), r.request_rate());
+            assert_eq!(s.visit_times(ua).collect::<Vec<_>>(), r.visit_times());
+
+            let now = SystemTime::UNIX_EPOCH;
+            assert_eq!(
+                s.next_request(ua, now, Some(now)),
+                r.next_request(now, Some(now))
+            );
+
+            let rules: Vec<_> = s.rules(ua).collect();
+            assert_eq!(rules, r.rules());
+            let positions = rules.iter().map(|u| u.position());
+            let expected = r.rules().iter().map(|u| u.position());
+            assert!(positions.eq(expected), "{ua}");
+        }
+
+        let clean_params: Vec<_> = s.clean_params().collect();
+        assert_eq!(clean_params, d.clean_params());
+        assert_eq!(clean_params[0].position(), d.clean_params()[0].position());
+        assert_eq!(s.host().as_ref(), d.host());
+
+        let sitemaps: Vec<_> = s.sitemaps().collect();
+        assert_eq!(sitemaps, vec!["https://example.com/sitemap.xml"]);
+        assert_eq!(s.truncated(), None);
+    }
+
+    #[test]
+    fn dialect() {
+        let options = ParserOptions::default().dialect(crate::parse::Dialect::Standard);
+        let d = Document::from_slice_with(TXT, &options);
+        let bytes = d.to_snapshot().unwrap();
+        let s = SnapshotRef::new(&bytes).unwrap();
+
+        assert_eq!(s.dialect(), Dialect::Standard);
+        assert_eq!(
+            s.is_allowed("FooBot/1.0", "/1"),
+            d.robots("FooBot/1.0").is_allowed("/1")
+        );
+        assert_eq!(s.crawl_delay("foobot"), None);
+    }
+
+    #[test]
+    fn empty() {
+        let d = Document::from_slice(b"");
+        let bytes = d.to_snapshot().unwrap();
+        let s = SnapshotRef::new(&bytes).unwrap();
+
+        assert_eq!(s.user_agents().collect::<Vec<_>>(), vec!["*"]);
+        assert_eq!(s.rules("foobot").count(), 0);
+        assert_eq!(s.request_rate("foobot"), None);
+        assert_eq!(s.visit_times("foobot").count(), 0);
+        assert_eq!(s.clean_params().count(), 0);
+        assert_eq!(s.host(), None);
+    }
+
+    #[test]
+    fn rejected() {
+        let bytes = Document::from_slice(TXT).to_snapshot().unwrap();
+
+        assert_eq!(
+            SnapshotRef::new(b"robots").unwrap_err(),
+            SnapshotError::Magic
+        );
+
+        let mut u = bytes.clone();
+        u[4] = 0;
+        let e = SnapshotRef::new(&u).unwrap_err();
+        assert_eq!(
+            e,
+            SnapshotError::Version {
+                expected: SNAPSHOT_VERSION,
+                found: 0
+            }
+        );
+
+        let u = &bytes[..bytes.len() - 1];
+        assert!(matches!(
+            SnapshotRef::new(u),
+            Err(SnapshotError::Length { .. })
+        ));
+
+        let mut u = bytes.clone();
+        *u.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            SnapshotRef::new(&u),
+            Err(SnapshotError::Checksum { .. })
+        ));
+    }
+
+    #[test]
+    fn malformed() {
+        let bytes = Document::from_slice(TXT).to_snapshot().unwrap();
+        let corrupt = |offset: usize, value: &[u8]| {
+            let mut bytes = bytes.clone();
+            let offset = HEADER_LEN + PREAMBLE_LEN + offset;
+            bytes[offset..offset + value.len()].copy_from_slice(value);
+            let sum = checksum(&bytes[HEADER_LEN..]);
+            bytes[12..16].copy_from_slice(&sum.to_le_bytes());
+            SnapshotRef::new(&bytes).map(|_| ())
+        };
+
+        // Points the first rule of the first agent out of bounds.
+        assert_eq!(corrupt(8, &[0xff]), Err(SnapshotError::Malformed));
+
+        // The nanoseconds carry into the maximum crawl-delay seconds.
+        let secs = u64::MAX.to_le_bytes();
+        let nanos = NANOS_PER_SEC.to_le_bytes();
+        assert_eq!(corrupt(16, &[secs, [0; 8]].concat()), Ok(()));
+        assert_eq!(
+            corrupt(16, &[&secs[..], &nanos[..]].concat()),
+            Err(SnapshotError::Malformed)
+        );
+
+        // The request-rate with the zero or carrying period.
+        let rate = |secs: u64, nanos: u32| {
+            let rate = [
+                &1u32.to_le_bytes()[..],
+                &secs.to_le_bytes(),
+                &nanos.to_le_bytes(),
+            ];
+            corrupt(28, &rate.concat())
+        };
+
+        assert_eq!(rate(10, 0), Ok(()));
+        assert_eq!(rate(0, 0), Err(SnapshotError::Malformed));
+        assert_eq!(rate(u64::MAX, NANOS_PER_SEC), Err(SnapshotError::Malformed));
+    }
+}
@@ -0,0 +1,217 @@
+use std::time::Duration;
+
+use crate::parse::{CleanParam, Document, Position, Rules, DEFAULT};
+use crate::snapshot::{checksum, dialect_to_byte, SnapshotError, SNAPSHOT_VERSION};
+use crate::snapshot::{CLEAN_PARAM_LEN, HEADER_LEN, MAGIC, NONE_U32, NONE_U64, PARAM_LEN};
+use crate::snapshot::{RULE_LEN, SITEMAP_LEN, VISIT_TIME_LEN};
+
+/// Returns the value as `u32` or fails if it does not fit the format.
+fn u32_of(u: usize) -> Result<u32, SnapshotError> {
+    u32::try_from(u).map_err(|_| SnapshotError::Overflow)
+}
+
+/// Returns the duration as nanoseconds or fails if it does not fit the format.
+fn nanos_of(u: Duration) -> Result<u64, SnapshotError> {
+    u64::try_from(u.as_nanos()).map_err(|_| SnapshotError::Overflow)
+}
+
+/// The `Writer` struct accumulates the record tables and the string blob.
+#[derive(Default)]
+struct Writer {
+    agents: Vec<u8>,
+    rules: Vec<u8>,
+    visit_times: Vec<u8>,
+    clean_params: Vec<u8>,
+    params: Vec<u8>,
+    sitemaps: Vec<u8>,
+    strings: Vec<u8>,
+}
+
+impl Writer {
+    /// Appends the string to the blob and returns its reference.
+    fn string(&mut self, u: &str) -> Result<[u8; 8], SnapshotError> {
+        let offset = u32_of(self.strings.len())?;
+        let len = u32_of(u.len())?;
+        self.strings.extend_from_slice(u.as_bytes());
+
+        let mut reference = [0; 8];
+        reference[..4].copy_from_slice(&offset.to_le_bytes());
+        reference[4..].copy_from_slice(&len.to_le_bytes());
+        Ok(reference)
+    }
+
+    /// Appends the position (if any) to the table.
+    fn position(table: &mut Vec<u8>, position: Option<Position>) -> Result<(), SnapshotError> {
+        let values = match position {
+            None => [0; 7],
+            Some(u) => [
+                1,
+                u32_of(u.line())?,
+                u32_of(u.offset())?,
+                u32_of(u.key().start())?,
+                u32_of(u.key().end())?,
+                u32_of(u.value().start())?,
+                u32_of(u.value().end())?,
+            ],
+        };
+
+        for value in values {
+            table.extend_from_slice(&value.to_le_bytes());
+        }
+
+        Ok(())
+    }
+
+    /// Appends the agent record with its rules and visit-times.
+    fn agent(&mut self, key: &str, rules: &Rules) -> Result<(), SnapshotError> {
+        let reference = self.string(key)?;
+        self.agents.extend_from_slice(&reference);
+        let first = u32_of(self.rules.len() / RULE_LEN)?;
+        self.agents.extend_from_slice(&first.to_le_bytes());
+        let count = u32_of(rules.rules().len())?;
+        self.agents.extend_from_slice(&count.to_le_bytes());
+
+        let delay = rules.crawl_delay();
+        let secs = delay.map_or(NONE_U64, |u| u.as_secs());
+        let nanos = delay.map_or(NONE_U32, |u| u.subsec_nanos());
+        self.agents.extend_from_slice(&secs.to_le_bytes());
+        self.agents.extend_from_slice(&nanos.to_le_bytes());
+
+        // Zero requests stand for the missing request-rate.
+        let rate = rules.request_rate();
+        let requests = rate.map_or(0, |u| u.requests());
+        let period = rate.map_or(Duration::ZERO, |u| u.period());
+        self.agents.extend_from_slice(&requests.to_le_bytes());
+        let (secs, nanos) = (period.as_secs(), period.subsec_nanos());
+        self.agents.extend_from_slice(&secs.to_le_bytes());
+        self.agents.extend_from_slice(&nanos.to_le_bytes());
+
+        let first = u32_of(self.visit_times.len() / VISIT_TIME_LEN)?;
+        self.agents.extend_from_slice(&first.to_le_bytes());
+        let count = u32_of(rules.visit_times().len())?;
+        self.agents.extend_from_slice(&count.to_le_bytes());
+
+        for rule in rules.rules() {
+            let reference = self.string(rule.pattern())?;
+            self.rules.extend_from_slice(&reference);
+            let permission = rule.is_allowed() as u8;
+            self.rules.extend_from_slice(&[permission, 0, 0, 0]);
+            Self::position(&mut self.rules, rule.position())?;
+        }
+
+        for visit_time in rules.visit_times() {
+            let start = nanos_of(visit_time.start())?;
+            self.visit_times.extend_from_slice(&start.to_le_bytes());
+            let end = nanos_of(visit_time.end())?;
+            self.visit_times.extend_from_slice(&end.to_le_bytes());
+        }
+
+        Ok(())
+    }
+
+    /// Appends the clean-param record with its parameters.
+    fn clean_param(&mut self, clean_param: &CleanParam) -> Result<(), SnapshotError> {
+        let reference = self.string(clean_param.path())?;
+        self.clean_params.extend_from_slice(&reference);
+        let first = u32_of(self.params.len() / PARAM_LEN)?;
+        self.clean_params.extend_from_slice(&first.to_le_bytes());
+        let count = u32_of(clean_param.params().len())?;
+        self.clean_params.extend_from_slice(&count.to_le_bytes());
+        Self::position(&mut self.clean_params, clean_param.position())?;
+
+        for param in clean_param.params() {
+            let reference = self.string(param)?;
+            self.params.extend_from_slice(&reference);
+        }
+
+        Ok(())
+    }
+}
+
+impl Document {
+    /// Returns the versioned binary snapshot of the merged rules of every
+    /// user-agent with their crawl-delays, request-rates and visit-times,
+    /// all sitemaps, clean-params and the host. See [crate::SnapshotRef].
+    /// Fails if the document exceeds the 4 GiB limit of the format.
+    ///
+    /// NOTE: Groups are stored merged per user-agent and non-standard
+    /// directives other than the listed ones are not preserved.
+    pub fn to_snapshot(&self) -> Result<Vec<u8>, SnapshotError> {
+        let dialect = self.dialect();
+
+        // The default user-agent always comes first.
+        let mut keys = vec![DEFAULT.to_string()];
+        for user_agent in self.user_agents() {
+            let key = dialect.agent_key(user_agent);
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+
+        let mut writer = Writer::default();
+        for key in &keys {
+            let (_, rules) = self.merge(key);
+            writer.agent(key, &rules)?;
+        }
+
+        for clean_param in self.clean_params() {
+            writer.clean_param(clean_param)?;
+        }
+
+        for sitemap in self.sitemaps() {
+            let reference = writer.string(sitemap.as_str())?;
+            writer.sitemaps.extend_from_slice(&reference);
+        }
+
+        let mut host = [0; 8];
+        host[..4].copy_from_slice(&NONE_U32.to_le_bytes());
+        if let Some(u) = self.host() {
+            host = writer.string(u.to_string().as_str())?;
+        }
+
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&u32_of(keys.len())?.to_le_bytes());
+        let rules = u32_of(writer.rules.len() / RULE_LEN)?;
+        payload.extend_from_slice(&rules.to_le_bytes());
+        let sitemaps = u32_of(writer.sitemaps.len() / SITEMAP_LEN)?;
+        payload.extend_from_slice(&sitemaps.to_le_bytes());
+        let truncated = self.truncated().map_or(NONE_U64, |u| u as u64);
+        payload.extend_from_slice(&truncated.to_le_bytes());
+        let visit_times = u32_of(writer.visit_times.len() / VISIT_TIME_LEN)?;
+        payload.extend_from_slice(&visit_times.to_le_bytes());
+        let clean_params = u32_of(writer.clean_params.len() / CLEAN_PARAM_LEN)?;
+        payload.extend_from_slice(&clean_params.to_le_bytes());
+        let params = u32_of(writer.params.len() / PARAM_LEN)?;
+        payload.extend_from_slice(&params.to_le_bytes());
+        payload.extend_from_slice(&host);
+
+        payload.extend_from_slice(&writer.agents);
+        payload.extend_from_slice(&writer.rules);
+        payload.extend_from_slice(&writer.visit_times);
+        payload.extend_from_slice(&writer.clean_params);
+        payload.extend_from_slice(&writer.params);
+        payload.extend_from_slice(&writer.sitemaps);
+        payload.extend_from_slice(&writer.strings);
+
+        let mut snapshot = Vec::with_capacity(HEADER_LEN + payload.len());
+        snapshot.extend_from_slice(MAGIC);
+        snapshot.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+        snapshot.extend_from_slice(&[dialect_to_byte(dialect), 0]);
+        snapshot.extend_from_slice(&u32_of(payload.len())?.to_le_bytes());
+        snapshot.extend_from_slice(&checksum(&payload).to_le_bytes());
+        snapshot.extend_from_slice(&payload);
+        Ok(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod writing {
+    use super::*;
+
+    #[test]
+    fn overflow() {
+        assert_eq!(u32_of(u32::MAX as usize), Ok(u32::MAX));
+        assert_eq!(u32_of(u32::MAX as usize + 1), Err(SnapshotError::Overflow));
+        assert_eq!(nanos_of(Duration::MAX), Err(SnapshotError::Overflow));
+    }
+}